@@ -0,0 +1,107 @@
+//! Times `prj ls` over a generated tree of a few thousand directories, searched with
+//! different numbers of threads, and rescanned once nothing has changed. Run with
+//! `cargo bench`.
+
+#[macro_use]
+extern crate bencher;
+
+use bencher::Bencher;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+const HOSTS: usize = 5;
+const OWNERS: usize = 20;
+const REPOS: usize = 20;
+
+/// Directories that hold other directories but no projects, which still have to be read
+const EMPTY_DIRS: usize = 10;
+
+/// Build the tree once for every benchmark: `host/owner/repo` projects like a real
+/// root, with some empty directories mixed in. It's left in the temp directory to be
+/// reused by the next run.
+fn tree() -> &'static Path {
+    static TREE: OnceLock<PathBuf> = OnceLock::new();
+
+    TREE.get_or_init(|| {
+        let root = env::temp_dir().join("prj-bench");
+        for host in 0..HOSTS {
+            let host = root.join(format!("host{}.com", host));
+            for owner in 0..OWNERS {
+                let owner = host.join(format!("owner{}", owner));
+                for repo in 0..REPOS {
+                    let repo = owner.join(format!("repo{}", repo));
+                    fs::create_dir_all(repo.join(".git")).unwrap();
+                    fs::create_dir_all(repo.join("src")).unwrap();
+                }
+                for empty in 0..EMPTY_DIRS {
+                    fs::create_dir_all(owner.join(format!("empty{}", empty))).unwrap();
+                }
+            }
+        }
+        root
+    })
+}
+
+/// A home directory with a config that searches the tree on `threads` threads
+fn home(threads: usize) -> PathBuf {
+    let home = tree().with_extension(format!("home-{}", threads));
+    fs::create_dir_all(&home).unwrap();
+    fs::write(
+        home.join(".prj"),
+        format!("root = {:?}\nthreads = {}\n", tree(), threads),
+    )
+    .unwrap();
+    home
+}
+
+fn list_projects(bench: &mut Bencher, threads: usize, full_rescan: bool) {
+    let home = home(threads);
+    let expected = HOSTS * OWNERS * REPOS;
+    let args: &[&str] = match full_rescan {
+        true => &["ls", "--full-rescan"],
+        false => &["ls"],
+    };
+
+    bench.iter(|| {
+        let output = Command::new(env!("CARGO_BIN_EXE_prj"))
+            .args(args)
+            .env("HOME", &home)
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout.split(|b| *b == b'\n').count() - 1, expected);
+    });
+}
+
+fn one_thread(bench: &mut Bencher) {
+    list_projects(bench, 1, true);
+}
+
+fn two_threads(bench: &mut Bencher) {
+    list_projects(bench, 2, true);
+}
+
+fn four_threads(bench: &mut Bencher) {
+    list_projects(bench, 4, true);
+}
+
+fn eight_threads(bench: &mut Bencher) {
+    list_projects(bench, 8, true);
+}
+
+/// Every directory is unchanged since the last iteration, so this is mostly `stat`
+fn unchanged_tree(bench: &mut Bencher) {
+    list_projects(bench, 1, false);
+}
+
+benchmark_group!(
+    benches,
+    one_thread,
+    two_threads,
+    four_threads,
+    eight_threads,
+    unchanged_tree
+);
+benchmark_main!(benches);
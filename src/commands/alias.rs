@@ -0,0 +1,52 @@
+use crate::config::{Config, WriteError};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum AliasError {
+    NotFound,
+    /// The path doesn't exist or isn't a directory
+    NotADirectory(PathBuf),
+    WriteError(WriteError),
+}
+
+/// The full path of a directory given on the command line, or the current directory.
+/// Gives back the path as given if it isn't a directory.
+pub fn resolve_directory(path: Option<&Path>) -> Result<PathBuf, PathBuf> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => env::current_dir().map_err(|_| PathBuf::from("."))?,
+    };
+    let path = fs::canonicalize(&path).map_err(|_| path.clone())?;
+    match path.is_dir() {
+        true => Ok(path),
+        false => Err(path),
+    }
+}
+
+/// Point `name` at a directory, the current one if no path is given. Replaces any
+/// existing alias with the same name.
+pub fn add(config: &mut Config, name: &str, path: Option<&Path>) -> Result<PathBuf, AliasError> {
+    let path = resolve_directory(path).map_err(AliasError::NotADirectory)?;
+
+    config.aliases.insert(name.to_string(), path.clone());
+    config.write_config().map_err(AliasError::WriteError)?;
+
+    Ok(path)
+}
+
+pub fn remove(config: &mut Config, name: &str) -> Result<(), AliasError> {
+    config.aliases.remove(name).ok_or(AliasError::NotFound)?;
+
+    config.write_config().map_err(AliasError::WriteError)
+}
+
+/// Print each alias and where it points, lined up in columns
+pub fn list(config: &Config) {
+    let width = config.aliases.keys().map(|n| n.len()).max().unwrap_or(0);
+
+    for (name, path) in &config.aliases {
+        println!("{:width$}  {}", name, path.display(), width = width);
+    }
+}
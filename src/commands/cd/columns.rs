@@ -0,0 +1,373 @@
+use crate::discovery::cache::VcsInfo;
+use crate::matching::MatchResult;
+use serde_derive::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Space between columns
+const GAP: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColumnKind {
+    /// Path to the project, or its name in the tree view
+    Path,
+    Branch,
+    /// Summary of the last commit
+    LastCommit,
+    /// Number of uncommitted changes
+    Changes,
+    /// Commits ahead of and behind the upstream branch
+    AheadBehind,
+    /// Time since the last commit or visit, whichever was more recent
+    LastActivity,
+    /// Where `origin` points
+    Remote,
+    /// Tags given to the project in the config or its `.prj.toml`
+    Tags,
+    /// Git tags on the current commit
+    GitTags,
+    /// Space taken up on disk
+    Size,
+    /// Which version control system the project uses
+    Vcs,
+    /// Language or tool, from the project's marker files
+    Type,
+}
+
+impl ColumnKind {
+    pub fn title(self) -> &'static str {
+        match self {
+            ColumnKind::Path => "Project",
+            ColumnKind::Branch => "Branch",
+            ColumnKind::LastCommit => "Last change",
+            ColumnKind::Changes => "Changes",
+            ColumnKind::AheadBehind => "Ahead/behind",
+            ColumnKind::LastActivity => "Last activity",
+            ColumnKind::Remote => "Remote",
+            ColumnKind::Tags => "Tags",
+            ColumnKind::GitTags => "Git tags",
+            ColumnKind::Size => "Size",
+            ColumnKind::Vcs => "VCS",
+            ColumnKind::Type => "Type",
+        }
+    }
+
+    /// Narrowest the column can be and still be useful. Columns that don't get
+    /// this much room are left off.
+    fn min_width(self) -> usize {
+        match self {
+            ColumnKind::Path | ColumnKind::LastCommit => 20,
+            ColumnKind::Remote => 16,
+            ColumnKind::Branch
+            | ColumnKind::Type
+            | ColumnKind::Tags
+            | ColumnKind::GitTags
+            | ColumnKind::LastActivity => 10,
+            ColumnKind::Changes | ColumnKind::AheadBehind => 8,
+            ColumnKind::Size | ColumnKind::Vcs => 6,
+        }
+    }
+
+    /// The text shown in this column for a project. The path column is drawn separately
+    /// since it depends on the view.
+    pub fn value(
+        self,
+        result: &MatchResult,
+        vcs_info: Option<&VcsInfo>,
+        size: Option<u64>,
+    ) -> String {
+        match (self, vcs_info) {
+            (ColumnKind::Branch, Some(vcs_info)) => vcs_info.current_branch_name.clone(),
+            (ColumnKind::LastCommit, Some(vcs_info)) => vcs_info.last_commit_summary.clone(),
+            (ColumnKind::Changes, Some(vcs_info)) => match vcs_info.uncommitted_changes {
+                0 => String::from("clean"),
+                n => format!("{} changed", n),
+            },
+            (ColumnKind::AheadBehind, Some(vcs_info)) => match vcs_info.ahead_behind {
+                Some((0, 0)) => String::from("="),
+                Some((ahead, behind)) => format!("↑{} ↓{}", ahead, behind),
+                None => String::new(),
+            },
+            (ColumnKind::Remote, Some(vcs_info)) => vcs_info
+                .remote_url
+                .as_ref()
+                .map_or_else(String::new, |url| short_url(url)),
+            (ColumnKind::GitTags, Some(vcs_info)) => vcs_info.head_tags.join(", "),
+            (ColumnKind::Tags, _) => result.project.tags.join(", "),
+            (ColumnKind::LastActivity, _) => {
+                let visited = result.last_visited.map(|t| t as i64);
+                match result.last_commit_time.max(visited) {
+                    Some(time) => format_age(time),
+                    None => String::new(),
+                }
+            }
+            (ColumnKind::Size, _) => size.map_or_else(String::new, format_size),
+            (ColumnKind::Vcs, _) => result.project.vcs.map_or("", |vcs| vcs.name()).to_string(),
+            (ColumnKind::Type, _) => result.project.project_type.clone().unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// A column in the `columns` list of the config
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ColumnConfig {
+    pub kind: ColumnKind,
+    /// Fixed width in characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    /// Share of the space left over once the fixed width columns are placed. Defaults to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<usize>,
+}
+
+impl ColumnConfig {
+    fn ratio(kind: ColumnKind, ratio: usize) -> ColumnConfig {
+        ColumnConfig {
+            kind,
+            width: None,
+            ratio: Some(ratio),
+        }
+    }
+
+    fn fixed(kind: ColumnKind, width: usize) -> ColumnConfig {
+        ColumnConfig {
+            kind,
+            width: Some(width),
+            ratio: None,
+        }
+    }
+
+    fn min_width(&self) -> usize {
+        self.width.unwrap_or_else(|| self.kind.min_width())
+    }
+}
+
+/// Used when the config doesn't list any columns. The type is only worth showing
+/// when there are marker files to give projects one.
+pub fn default_columns(with_type: bool) -> Vec<ColumnConfig> {
+    let mut columns = vec![
+        ColumnConfig::ratio(ColumnKind::Path, 6),
+        ColumnConfig::ratio(ColumnKind::LastCommit, 4),
+        ColumnConfig::fixed(ColumnKind::Branch, 16),
+        ColumnConfig::fixed(ColumnKind::Changes, 10),
+        ColumnConfig::fixed(ColumnKind::Vcs, 6),
+    ];
+    if with_type {
+        columns.push(ColumnConfig::fixed(ColumnKind::Type, 10));
+    }
+    columns
+}
+
+/// A column placed on screen
+pub struct Placed {
+    pub kind: ColumnKind,
+    pub start: usize,
+    pub width: usize,
+}
+
+pub struct Layout {
+    pub columns: Vec<Placed>,
+    /// Whether some columns didn't fit, in which case the last column cycles through them
+    pub cycles: bool,
+}
+
+/// Fit as many columns as possible into `width` characters starting at `start`. The
+/// columns that don't fit share the last slot, and `cycle` picks which one is shown.
+pub fn layout(columns: &[ColumnConfig], start: usize, width: usize, cycle: usize) -> Layout {
+    let mut used = 0;
+    let fits = columns
+        .iter()
+        .take_while(|column| {
+            used += column.min_width() + GAP;
+            used <= width + GAP
+        })
+        .count()
+        .max(1)
+        .min(columns.len());
+
+    let mut shown: Vec<ColumnConfig> = columns.iter().take(fits).cloned().collect();
+    let overflow = &columns[fits.saturating_sub(1)..];
+    let cycles = overflow.len() > 1;
+    if cycles {
+        // Keep the size of the slot so the layout doesn't jump around while cycling
+        shown[fits - 1].kind = overflow[cycle % overflow.len()].kind;
+    }
+
+    // Fixed widths first, then the rest is shared out by ratio
+    let available = width.saturating_sub(GAP * fits.saturating_sub(1));
+    let fixed: usize = shown.iter().filter_map(|c| c.width).sum();
+    let ratio_total: usize = shown
+        .iter()
+        .filter(|c| c.width.is_none())
+        .map(|c| c.ratio.unwrap_or(1))
+        .sum();
+    let shared = available.saturating_sub(fixed);
+
+    let mut placed = vec![];
+    let mut x = start;
+    for column in shown {
+        let column_width = match column.width {
+            Some(w) => w,
+            None if ratio_total > 0 => shared * column.ratio.unwrap_or(1) / ratio_total,
+            None => 0,
+        };
+        // A fixed width can be wider than the screen
+        let column_width = column_width.min((start + width).saturating_sub(x));
+        placed.push(Placed {
+            kind: column.kind,
+            start: x,
+            width: column_width,
+        });
+        x += column_width + GAP;
+    }
+
+    // Rounding leaves a few characters spare, give them to the last column
+    if let Some(last) = placed.last_mut() {
+        last.width += (start + width).saturating_sub(last.start + last.width);
+    }
+
+    Layout {
+        columns: placed,
+        cycles,
+    }
+}
+
+fn short_url(url: &str) -> String {
+    let url = url.trim_end_matches(".git");
+    let url = url.splitn(2, "://").last().unwrap_or(url);
+    url.trim_start_matches("git@").to_string()
+}
+
+fn format_age(timestamp: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let seconds = (now - timestamp).max(0);
+
+    let (count, unit) = match seconds {
+        s if s < 60 => return String::from("just now"),
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 60 * 60 * 24 => (s / (60 * 60), "hour"),
+        s if s < 60 * 60 * 24 * 7 => (s / (60 * 60 * 24), "day"),
+        s if s < 60 * 60 * 24 * 30 => (s / (60 * 60 * 24 * 7), "week"),
+        s if s < 60 * 60 * 24 * 365 => (s / (60 * 60 * 24 * 30), "month"),
+        s => (s / (60 * 60 * 24 * 365), "year"),
+    };
+    match count {
+        1 => format!("1 {} ago", unit),
+        n => format!("{} {}s ago", n, unit),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "K", "M", "G", "T"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{}{}", bytes, UNITS[0]),
+        _ => format!("{:.1}{}", size, UNITS[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(layout: &Layout) -> Vec<ColumnKind> {
+        layout.columns.iter().map(|column| column.kind).collect()
+    }
+
+    /// Side by side with a gap between, filling exactly the space given
+    fn assert_fills(layout: &Layout, start: usize, width: usize) {
+        let mut x = start;
+        for column in &layout.columns {
+            assert_eq!(column.start, x);
+            x += column.width + GAP;
+        }
+        if !layout.columns.is_empty() {
+            assert_eq!(x - GAP, start + width);
+        }
+    }
+
+    #[test]
+    fn every_column_fits() {
+        let columns = default_columns(false);
+        let layout = layout(&columns, 2, 100, 0);
+        assert_eq!(
+            kinds(&layout),
+            columns.iter().map(|c| c.kind).collect::<Vec<_>>()
+        );
+        assert!(!layout.cycles);
+        assert_eq!(layout.columns[2].width, 16);
+        assert_fills(&layout, 2, 100);
+    }
+
+    #[test]
+    fn columns_that_dont_fit_cycle_through_the_last_slot() {
+        let columns = default_columns(false);
+        let first = layout(&columns, 2, 50, 0);
+        assert!(first.cycles);
+        assert_eq!(
+            kinds(&first),
+            vec![ColumnKind::Path, ColumnKind::LastCommit]
+        );
+        assert_fills(&first, 2, 50);
+
+        let overflow = [
+            ColumnKind::Branch,
+            ColumnKind::Changes,
+            ColumnKind::Vcs,
+            ColumnKind::LastCommit,
+        ];
+        for (cycle, &kind) in (1..).zip(&overflow) {
+            let cycled = layout(&columns, 2, 50, cycle);
+            assert_eq!(cycled.columns[1].kind, kind);
+            // The slot keeps its size, so nothing jumps around
+            assert_eq!(cycled.columns[1].width, first.columns[1].width);
+        }
+    }
+
+    #[test]
+    fn no_columns() {
+        let layout = layout(&[], 2, 80, 3);
+        assert!(layout.columns.is_empty());
+        assert!(!layout.cycles);
+    }
+
+    #[test]
+    fn fixed_widths_wider_than_the_screen() {
+        let columns = [ColumnConfig::fixed(ColumnKind::Path, 100)];
+        let one = layout(&columns, 2, 40, 0);
+        assert_eq!(kinds(&one), vec![ColumnKind::Path]);
+        assert_eq!(one.columns[0].width, 40);
+
+        let columns = [
+            ColumnConfig::fixed(ColumnKind::Path, 30),
+            ColumnConfig::fixed(ColumnKind::Branch, 30),
+        ];
+        let cycled = layout(&columns, 2, 40, 1);
+        assert!(cycled.cycles);
+        assert_eq!(kinds(&cycled), vec![ColumnKind::Branch]);
+        assert_eq!(cycled.columns[0].width, 40);
+    }
+
+    #[test]
+    fn nothing_is_placed_past_the_edge() {
+        let columns = default_columns(true);
+        for width in 0..120 {
+            for cycle in 0..3 {
+                let layout = layout(&columns, 2, width, cycle);
+                assert!(!layout.columns.is_empty());
+                for column in &layout.columns {
+                    assert!(column.start + column.width <= 2 + width, "{}", width);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,899 @@
+use super::print_path;
+use crate::config::Config;
+use crate::daemon::client::fetch_projects;
+use crate::discovery::cache::{Cache, CacheClient, Project};
+use crate::discovery::scan_roots;
+use crate::discovery::size::directory_size;
+use crate::discovery::vcs::fetch_project_info;
+use crate::history::History;
+use crate::matching::sort::{sort_results, SortMode};
+use crate::matching::{CaseMode, MatchMode, MatchResult, Matcher};
+use columns::{default_columns, layout, ColumnConfig, ColumnKind};
+use crossterm::{
+    event::{poll, read, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent},
+    style::Color,
+};
+use screen::Screen;
+use std::collections::HashSet;
+use std::fs;
+use std::io::stderr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use terminal::TerminalGuard;
+use text::{display_width, fit_width, truncate_beginning, truncate_end};
+use theme::Theme;
+use tree::{flat_rows, tree_rows, Group, Row, View};
+
+pub mod columns;
+mod screen;
+mod terminal;
+mod text;
+pub mod theme;
+pub mod tree;
+
+struct UiState {
+    query: String,
+    results: Vec<MatchResult>,
+    /// Lines of the list, pointing into `results`
+    rows: Vec<Row>,
+    selected_index: usize,
+    /// Index of the first row in the visible part of the list
+    scroll_offset: usize,
+    view: View,
+    /// Keys of the groups that have been collapsed in the tree view
+    collapsed: HashSet<String>,
+    pinned: Vec<PathBuf>,
+    columns: Vec<ColumnConfig>,
+    /// Which of the columns that don't fit on screen is shown in the last column
+    column_cycle: usize,
+    match_mode: MatchMode,
+    case_mode: CaseMode,
+    sort_mode: SortMode,
+    theme: Theme,
+}
+
+/// Options for a single run of the picker, from the command line
+pub struct ListOptions {
+    /// Text to start the query with
+    pub query: String,
+    /// Print the project straight away when exactly one matches the query
+    pub select_one: bool,
+    /// Exit straight away when nothing matches the query
+    pub exit_zero: bool,
+    /// Read every directory, even the ones that haven't changed since the last search
+    pub full_rescan: bool,
+}
+
+pub enum CommandError {
+    NothingSelected,
+    /// stderr isn't a terminal, so there's nowhere to draw the picker
+    NotATerminal,
+    RenderError(crossterm::ErrorKind),
+}
+
+impl UiState {
+    /// The tree is only shown while there's no query, typing falls back to the flat list
+    fn is_tree(&self) -> bool {
+        self.view == View::Tree && self.query.is_empty()
+    }
+
+    fn select_next(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+        if self.selected_index >= self.rows.len() - 1 {
+            self.selected_index = 0;
+        } else {
+            self.selected_index += 1;
+        }
+    }
+
+    fn select_prev(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+        if self.selected_index == 0 {
+            self.selected_index = self.rows.len() - 1;
+        } else {
+            self.selected_index -= 1;
+        }
+    }
+
+    /// The flat list is drawn from the bottom up, so the first row is closest to the prompt
+    fn select_up(&mut self) {
+        match self.is_tree() {
+            true => self.select_prev(),
+            false => self.select_next(),
+        }
+    }
+
+    fn select_down(&mut self) {
+        match self.is_tree() {
+            true => self.select_next(),
+            false => self.select_prev(),
+        }
+    }
+
+    fn toggle_view(&mut self) {
+        self.view = match self.view {
+            View::Flat => View::Tree,
+            View::Tree => View::Flat,
+        };
+        self.selected_index = 0;
+    }
+
+    /// Collapse the selected group, or jump to the group containing the selected row
+    fn collapse_selected(&mut self) {
+        let selected = match self.rows.get(self.selected_index) {
+            Some(row) => row,
+            None => return,
+        };
+
+        if let Row::Group(group) = selected {
+            if group.expanded {
+                self.collapsed.insert(group.key.clone());
+                return;
+            }
+        }
+
+        // The flat list's pinned heading comes after its projects, since it's drawn
+        // from the bottom up, so looking back would only find it from the wrong side
+        if !self.is_tree() {
+            return;
+        }
+        let depth = selected.depth();
+        let parent = self.rows[..self.selected_index]
+            .iter()
+            .rposition(|row| matches!(row, Row::Group(g) if g.depth < depth));
+        if let Some(parent) = parent {
+            self.selected_index = parent;
+        }
+    }
+
+    fn expand_selected(&mut self) {
+        if let Some(Row::Group(group)) = self.rows.get(self.selected_index) {
+            self.collapsed.remove(&group.key);
+        }
+    }
+
+    fn toggle_selected_group(&mut self) {
+        if let Some(Row::Group(group)) = self.rows.get(self.selected_index) {
+            if !self.collapsed.remove(&group.key) {
+                self.collapsed.insert(group.key.clone());
+            }
+        }
+    }
+
+    fn selected_result(&self) -> Option<&MatchResult> {
+        match self.rows.get(self.selected_index) {
+            Some(Row::Project { index, .. }) => self.results.get(*index),
+            _ => None,
+        }
+    }
+
+    /// Open the selected project, or expand or collapse the selected group
+    fn activate_selected(&mut self) -> Option<PathBuf> {
+        match self.selected_result() {
+            Some(result) => Some(result.project.path.clone()),
+            None => {
+                self.toggle_selected_group();
+                None
+            }
+        }
+    }
+
+    /// Index of the row drawn at `screen_row`, if it's part of the list
+    fn row_at(&self, screen_size: (u16, u16), screen_row: u16) -> Option<usize> {
+        if is_compact(screen_size) {
+            return None;
+        }
+
+        let height = list_height(screen_size);
+        let screen_row = screen_row as usize;
+        if screen_row < 1 || screen_row > height {
+            return None;
+        }
+        let offset = match self.is_tree() {
+            true => screen_row - 1,
+            false => height - screen_row,
+        };
+        let index = self.scroll_offset + offset;
+        match index < self.rows.len() {
+            true => Some(index),
+            false => None,
+        }
+    }
+
+    /// Pin the selected project, or unpin it if it's already pinned, and save the pins.
+    /// The config is read again so only the pins are saved, not the options given on
+    /// the command line, and the path is kept the way `prj pin` keeps it.
+    fn toggle_pin_selected(&mut self, config: &Config) {
+        let path = match self.selected_result() {
+            Some(result) => fs::canonicalize(&result.project.path)
+                .unwrap_or_else(|_| result.project.path.clone()),
+            None => return,
+        };
+        // Not being able to save shouldn't interrupt picking a project
+        let mut saved = match Config::load_from(&config.location) {
+            Ok(saved) => saved,
+            Err(_) => return,
+        };
+
+        match saved.pinned.iter().position(|p| *p == path) {
+            Some(position) => {
+                saved.pinned.remove(position);
+            }
+            None => saved.pinned.push(path),
+        }
+
+        if saved.write_config().is_ok() {
+            self.pinned = saved.pinned;
+        }
+    }
+
+    fn update_rows(&mut self) {
+        self.rows = match self.is_tree() {
+            true => tree_rows(&self.results, &self.collapsed),
+            false => flat_rows(&self.results, &self.collapsed, self.query.is_empty()),
+        };
+        // The list can shrink under the selection, eg. when a group is collapsed
+        self.selected_index = self.selected_index.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Scroll the list as little as possible to bring the selection into a
+    /// viewport of `height` rows, without leaving empty space past the last row
+    fn scroll_into_view(&mut self, height: usize) {
+        let max_offset = self.rows.len().saturating_sub(height);
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if height > 0 && self.selected_index >= self.scroll_offset + height {
+            self.scroll_offset = self.selected_index + 1 - height;
+        }
+        self.scroll_offset = self.scroll_offset.min(max_offset);
+    }
+
+    fn add_char(&mut self, c: char) {
+        self.query.push(c);
+        self.selected_index = 0;
+    }
+
+    fn remove_char(&mut self) {
+        self.query.pop();
+        self.selected_index = 0;
+    }
+
+    fn cycle_last_column(&mut self) {
+        self.column_cycle = self.column_cycle.wrapping_add(1);
+    }
+
+    fn cycle_match_mode(&mut self) {
+        self.match_mode = self.match_mode.next();
+        self.selected_index = 0;
+    }
+
+    fn cycle_case_mode(&mut self) {
+        self.case_mode = self.case_mode.next();
+        self.selected_index = 0;
+    }
+
+    fn cycle_sort_mode(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+        self.selected_index = 0;
+    }
+}
+
+/// Limit on how often background discovery can trigger a redraw, roughly 30 frames a second
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Longest gap between two clicks on the same row for them to count as a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Smallest terminal that fits the heading, a result, the footer and the prompt.
+/// Anything smaller gets the compact layout.
+const MIN_ROWS: u16 = 4;
+const MIN_COLS: u16 = 20;
+
+/// Number of list rows that fit on a screen of the given size, between the heading
+/// and the footer. The compact layout only has room for the selection.
+fn list_height(screen_size: (u16, u16)) -> usize {
+    match is_compact(screen_size) {
+        true => 1,
+        false => (screen_size.1 - 3) as usize,
+    }
+}
+
+fn is_compact((cols, rows): (u16, u16)) -> bool {
+    rows < MIN_ROWS || cols < MIN_COLS
+}
+
+fn root_label(root: &Path) -> String {
+    root.file_name()
+        .unwrap_or(root.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The text shown for a project and matched against the query. Paths are relative
+/// to their root so the common prefix doesn't pollute the scores, and are labelled
+/// with the root's name when there is more than one. Aliased directories outside
+/// every root show their full path.
+pub fn display_path(project: &Project, with_root_label: bool) -> String {
+    // Worktrees can be found through their repository wherever they are
+    if project.root.as_os_str().is_empty() || !project.path.starts_with(&project.root) {
+        return project.path.to_string_lossy().to_string();
+    }
+
+    let relative = project.relative_path().to_string_lossy();
+
+    match with_root_label {
+        true => format!("{}:{}", root_label(&project.root), relative),
+        false => relative.to_string(),
+    }
+}
+
+/// Colours for a row of the list
+fn row_colors(theme: &Theme, is_selected: bool, has_pending_changes: bool) -> (Color, Color) {
+    let fg = match (has_pending_changes, is_selected) {
+        (true, _) => theme.dirty_fg,
+        (false, true) => theme.highlight_fg,
+        (false, false) => theme.text_fg,
+    };
+    let bg = match is_selected {
+        true => theme.highlight_bg,
+        false => Color::Reset,
+    };
+    (fg, bg)
+}
+
+fn render_selection_marker(
+    screen: &mut Screen,
+    theme: &Theme,
+    is_selected: bool,
+    has_pending_changes: bool,
+    row: u16,
+) {
+    let (fg, bg) = row_colors(theme, is_selected, has_pending_changes);
+    let (cols, _) = screen.size();
+
+    screen.fill(0, row, cols, bg);
+    let marker = match (is_selected, has_pending_changes) {
+        (true, true) => ">*",
+        (true, false) => "> ",
+        (false, true) => "*",
+        (false, false) => " ",
+    };
+    screen.print(0, row, marker, fg, bg);
+}
+
+fn render_group(screen: &mut Screen, theme: &Theme, group: &Group, is_selected: bool, row: u16) {
+    render_selection_marker(screen, theme, is_selected, false, row);
+
+    let dirty = match group.dirty_count {
+        0 => String::new(),
+        n => format!(", {} dirty", n),
+    };
+    let (fg, bg) = match is_selected {
+        true => (theme.highlight_fg, theme.highlight_bg),
+        false => (theme.group_fg, Color::Reset),
+    };
+    let toggle = match group.expanded {
+        true => "▾",
+        false => "▸",
+    };
+    let counts = format!(" ({}{})", group.count, dirty);
+
+    // Shorten the name rather than the counts if there isn't room for both
+    let (cols, _) = screen.size();
+    let indent = 2 + group.depth * 2;
+    let name_width = (cols as usize).saturating_sub(indent + 2 + display_width(&counts));
+    let heading = format!(
+        "{} {}{}",
+        toggle,
+        truncate_end(&group.name, name_width),
+        counts
+    );
+    screen.print(indent as u16, row, &heading, fg, bg);
+}
+
+/// Draw a scrollbar in the last column when the list doesn't fit. `top` is the screen
+/// row of the first list row, and the flat list's bar is flipped to match its order.
+fn render_scrollbar(state: &UiState, screen: &mut Screen, top: u16, height: usize) {
+    let total = state.rows.len();
+    if total <= height {
+        return;
+    }
+
+    let (cols, _) = screen.size();
+    let thumb_size = (height * height / total).max(1);
+    let thumb_start = state.scroll_offset * (height - thumb_size) / (total - height);
+    for offset in 0..height {
+        let in_thumb = offset >= thumb_start && offset < thumb_start + thumb_size;
+        let symbol = match in_thumb {
+            true => "█",
+            false => "│",
+        };
+        let row = match state.is_tree() {
+            true => top + offset as u16,
+            false => top + (height - 1 - offset) as u16,
+        };
+        screen.print(
+            cols - 1,
+            row,
+            symbol,
+            state.theme.scrollbar_fg,
+            Color::Reset,
+        );
+    }
+}
+
+fn render_prompt(state: &UiState, screen: &mut Screen, prompt_row: u16) {
+    let (cols, _) = screen.size();
+
+    // Keep the end of a long query in view, it's where the typing happens
+    let query = truncate_beginning(&state.query, (cols as usize).saturating_sub(3));
+    let theme = &state.theme;
+    screen.print(0, prompt_row, ">", theme.prompt_fg, theme.prompt_bg);
+    screen.print(2, prompt_row, &query, theme.prompt_fg, theme.prompt_bg);
+    screen.set_cursor(2 + display_width(&query) as u16, prompt_row);
+}
+
+/// Layout for terminals too small for the list, showing only the selection and the prompt
+fn render_compact(state: &UiState, screen: &mut Screen) {
+    let (cols, rows) = screen.size();
+    let prompt_row = rows - 1;
+
+    if prompt_row > 0 {
+        let selected = match state.rows.get(state.selected_index) {
+            Some(Row::Group(group)) => group.name.as_str(),
+            Some(Row::Project { index, .. }) => state.results[*index].display.as_str(),
+            None => "",
+        };
+        screen.print(
+            0,
+            prompt_row - 1,
+            &truncate_beginning(selected, cols as usize),
+            state.theme.highlight_fg,
+            state.theme.highlight_bg,
+        );
+    }
+
+    render_prompt(state, screen, prompt_row);
+}
+
+fn render(state: &UiState, cache: &CacheClient, screen: &mut Screen) {
+    let (cols, rows) = screen.size();
+    if cols == 0 || rows == 0 {
+        return;
+    }
+    if is_compact((cols, rows)) {
+        render_compact(state, screen);
+        return;
+    }
+    let height = list_height((cols, rows));
+    // Leave room for the selection marker, and the last column free for the scrollbar
+    let layout = layout(&state.columns, 2, cols as usize - 3, state.column_cycle);
+
+    let visible_rows = state.rows.iter().enumerate().skip(state.scroll_offset);
+    for (offset, (i, list_row)) in visible_rows.take(height).enumerate() {
+        // The tree reads top down, the flat list bottom up so the best match is by the prompt
+        let row = match state.is_tree() {
+            true => 1 + offset as u16,
+            false => height as u16 - offset as u16,
+        };
+        let is_selected = i == state.selected_index;
+
+        match list_row {
+            Row::Group(group) => render_group(screen, &state.theme, group, is_selected, row),
+            Row::Project { index, depth, name } => {
+                let result = &state.results[*index];
+                let vcs_info = cache.get_vcs_info(&result.project.path);
+                let size = cache.get_size(&result.project.path);
+                let (fg, bg) = row_colors(&state.theme, is_selected, result.dirty);
+
+                render_selection_marker(screen, &state.theme, is_selected, result.dirty, row);
+                for column in &layout.columns {
+                    let (start, text) = match column.kind {
+                        ColumnKind::Path => {
+                            let indent = (depth * 2).min(column.width);
+                            let width = column.width - indent;
+                            let mut name = match &result.alias {
+                                Some(alias) => format!("{} ({})", name, alias),
+                                None => name.clone(),
+                            };
+                            if let Some(worktree) = &result.project.worktree {
+                                let branch = worktree.branch.as_deref().unwrap_or("detached");
+                                name = format!("{} [{}]", name, branch);
+                            }
+                            if result.project.superproject.is_some() {
+                                let out_of_sync = vcs_info.as_ref().and_then(|v| v.out_of_sync);
+                                name = match out_of_sync {
+                                    Some(true) => format!("{} [submodule, out of sync]", name),
+                                    _ => format!("{} [submodule]", name),
+                                };
+                            }
+                            (column.start + indent, truncate_beginning(&name, width))
+                        }
+                        kind => {
+                            let value = kind.value(result, vcs_info.as_ref(), size);
+                            (column.start, truncate_end(&value, column.width))
+                        }
+                    };
+                    screen.print(start as u16, row, &text, fg, bg);
+                }
+            }
+        }
+    }
+
+    render_scrollbar(state, screen, 1, height);
+
+    // Render heading
+    screen.fill(0, 0, cols, state.theme.heading_bg);
+    for (i, column) in layout.columns.iter().enumerate() {
+        let is_last = i == layout.columns.len() - 1;
+        let title = match layout.cycles && is_last {
+            true => format!("{}   <TAB>", column.kind.title()),
+            false => column.kind.title().to_string(),
+        };
+        screen.print(
+            column.start as u16,
+            0,
+            &fit_width(&title, column.width),
+            state.theme.heading_fg,
+            state.theme.heading_bg,
+        );
+    }
+
+    let prompt_row = rows - 1;
+    screen.print(
+        2,
+        prompt_row - 1,
+        &format!(
+            "{}  {} {}  sort: {}",
+            state.results.len(),
+            state.match_mode.name(),
+            state.case_mode.name(),
+            state.sort_mode.name()
+        ),
+        state.theme.footer_fg,
+        Color::Reset,
+    );
+    render_prompt(state, screen, prompt_row);
+}
+
+/// Match every project found so far against the query, best first
+fn find_matches(
+    state: &UiState,
+    cache: &CacheClient,
+    config: &Config,
+    history: &History,
+) -> Vec<MatchResult> {
+    let roots = config.all_roots();
+    let show_root_labels = roots.len() > 1;
+    let matcher = Matcher::new(&state.query, state.match_mode, state.case_mode);
+
+    let mut results = vec![];
+    for proj in cache.get_projects() {
+        let display = display_path(&proj, show_root_labels);
+        let alias = config.alias_for(&proj.path);
+        let tags = config.project_tags(&proj);
+        let match_score = matcher.score_project(&display, alias, &tags);
+        if let Some(score) = match_score {
+            let vcs_info = cache.get_vcs_info(&proj.path);
+            results.push(MatchResult {
+                score,
+                display,
+                // Aliased directories outside every root go after all of the roots
+                root_index: roots
+                    .iter()
+                    .position(|r| *r == proj.root)
+                    .unwrap_or(roots.len()),
+                dirty: vcs_info.as_ref().is_some_and(|v| v.uncommitted_changes > 0),
+                last_commit_time: vcs_info.as_ref().map(|v| v.last_commit_time),
+                last_visited: history.last_visited(&proj.path),
+                alias: alias.map(String::from),
+                pinned: state.pinned.contains(&config.stored_path(&proj.path)),
+                project: Project { tags, ..proj },
+            });
+        }
+    }
+
+    sort_results(&mut results, state.sort_mode);
+    results
+}
+
+/// Record the visit and print the path for the shell to use
+fn select_project(history: &mut History, path: &Path) {
+    history.record_visit(path);
+    // Failing to save the history shouldn't stop the selection from being used
+    let _ = history.write();
+
+    print_path(path);
+}
+
+pub fn run(config: &Config, options: &ListOptions) -> Result<(), CommandError> {
+    let cache = Cache::new();
+
+    let mut cache = cache.share();
+    let mut cache2 = cache.clone();
+
+    let mut history = History::autoload();
+    let columns = match config.columns.is_empty() {
+        true => default_columns(!config.markers.is_empty()),
+        false => config.columns.clone(),
+    };
+    let measure_sizes = columns.iter().any(|c| c.kind == ColumnKind::Size);
+    let (scanned_tx, scanned_rx) = mpsc::channel();
+    let scan_config = config.clone();
+    let full_rescan = options.full_rescan;
+    thread::spawn(move || {
+        // The daemon already knows everything, and keeps it up to date
+        if let Some(snapshot) = fetch_projects(full_rescan) {
+            for entry in snapshot.projects {
+                let path = entry.project.path.clone();
+                cache2.add_project(entry.project);
+                if let Some(vcs_info) = entry.vcs_info {
+                    cache2.add_vcs_info(&path, vcs_info);
+                }
+                if let Some(size) = entry.size {
+                    cache2.add_size(&path, size);
+                }
+            }
+            let _ = scanned_tx.send(());
+            return;
+        }
+
+        // Projects show up in the list as soon as they're found
+        scan_roots(&scan_config, full_rescan, |project| {
+            cache2.add_project(project)
+        });
+        // Nobody is listening unless the query has to be checked before drawing
+        let _ = scanned_tx.send(());
+
+        // Projects found by a marker file alone have nothing to fetch
+        // TODO: Record a failure to read VCS info for a project
+        for p in cache2.get_projects() {
+            if let Some(vcs_info) = fetch_project_info(&p) {
+                cache2.add_vcs_info(&p.path, vcs_info);
+            }
+        }
+
+        // Walking every file is slow, so only do it if the size is going to be shown
+        if measure_sizes {
+            for p in cache2.get_projects() {
+                cache2.add_size(&p.path, directory_size(&p.path));
+            }
+        }
+    });
+
+    let mut exit = false;
+    let mut ui_state = UiState {
+        columns,
+        column_cycle: 0,
+        match_mode: config.match_mode,
+        case_mode: config.case_mode,
+        sort_mode: config.sort,
+        query: options.query.clone(),
+        results: vec![],
+        rows: vec![],
+        selected_index: 0,
+        scroll_offset: 0,
+        view: config.view,
+        collapsed: HashSet::new(),
+        pinned: config.pinned.clone(),
+        theme: Theme::from_config(&config.theme, config.color),
+    };
+
+    // Decide whether the picker is needed at all once every project has been found
+    if options.select_one || options.exit_zero {
+        let _ = scanned_rx.recv();
+        let results = find_matches(&ui_state, &cache, config, &history);
+        match results.len() {
+            0 if options.exit_zero => return Err(CommandError::NothingSelected),
+            1 if options.select_one => {
+                select_project(&mut history, &results[0].project.path);
+                return Ok(());
+            }
+            _ => (),
+        }
+    }
+
+    if !terminal::is_interactive() {
+        return Err(CommandError::NotATerminal);
+    }
+
+    let mut selected_project = None;
+    // Row and time of the last click, to spot double clicks
+    let mut last_click: Option<(usize, Instant)> = None;
+    let mut terminal_size = terminal::size();
+    let mut screen = Screen::new(terminal_size.0, terminal_size.1);
+
+    let guard = TerminalGuard::enter().map_err(CommandError::RenderError)?;
+    while !exit {
+        ui_state.results = find_matches(&ui_state, &cache, config, &history);
+        ui_state.update_rows();
+        ui_state.scroll_into_view(list_height(terminal_size));
+
+        screen.resize(terminal_size.0, terminal_size.1);
+        render(&ui_state, &cache, &mut screen);
+        screen
+            .flush(&mut stderr())
+            .map_err(CommandError::RenderError)?;
+        let last_frame = Instant::now();
+
+        // Input is handled straight away, but new data from the background scan waits
+        // for the next frame so a bulk scan doesn't redraw on every project found
+        let mut input_available: bool = false;
+        let mut has_new_data = false;
+        while !input_available {
+            has_new_data = has_new_data || cache.has_new_data();
+            if has_new_data && last_frame.elapsed() >= FRAME_INTERVAL {
+                break;
+            }
+            input_available = poll(Duration::from_millis(10)).map_err(CommandError::RenderError)?;
+        }
+
+        if input_available {
+            let read_result = read().map_err(CommandError::RenderError)?;
+
+            match read_result {
+                Event::Key(event) => match (event.code, event.modifiers) {
+                    (KeyCode::Char('c'), KeyModifiers::CONTROL) => exit = true,
+                    (KeyCode::Char('f'), KeyModifiers::CONTROL) => ui_state.cycle_match_mode(),
+                    (KeyCode::Char('e'), KeyModifiers::CONTROL) => ui_state.cycle_case_mode(),
+                    (KeyCode::Char('s'), KeyModifiers::CONTROL) => ui_state.cycle_sort_mode(),
+                    (KeyCode::Char('t'), KeyModifiers::CONTROL) => ui_state.toggle_view(),
+                    (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                        ui_state.toggle_pin_selected(config)
+                    }
+                    (KeyCode::Char(c), _) => ui_state.add_char(c),
+                    (KeyCode::Backspace, _) => ui_state.remove_char(),
+                    (KeyCode::Down, _) => ui_state.select_down(),
+                    (KeyCode::Up, _) => ui_state.select_up(),
+                    (KeyCode::Left, _) => ui_state.collapse_selected(),
+                    (KeyCode::Right, _) => ui_state.expand_selected(),
+                    (KeyCode::Esc, _) => exit = true,
+                    (KeyCode::Tab, _) => ui_state.cycle_last_column(),
+                    (KeyCode::Enter, _) => {
+                        selected_project = ui_state.activate_selected();
+                        exit = selected_project.is_some();
+                    }
+                    _ => (),
+                },
+                Event::Mouse(event) => match event {
+                    MouseEvent::Down(MouseButton::Left, _, 0, _) if !is_compact(terminal_size) => {
+                        ui_state.cycle_last_column()
+                    }
+                    MouseEvent::Down(MouseButton::Left, _, row, _) => {
+                        if let Some(index) = ui_state.row_at(terminal_size, row) {
+                            let is_double_click = last_click.is_some_and(|(last, time)| {
+                                last == index && time.elapsed() < DOUBLE_CLICK_INTERVAL
+                            });
+                            ui_state.selected_index = index;
+
+                            if is_double_click {
+                                last_click = None;
+                                selected_project = ui_state.activate_selected();
+                                exit = selected_project.is_some();
+                            } else {
+                                last_click = Some((index, Instant::now()));
+                            }
+                        }
+                    }
+                    MouseEvent::ScrollUp(_, _, _) => ui_state.select_up(),
+                    MouseEvent::ScrollDown(_, _, _) => ui_state.select_down(),
+                    _ => (),
+                },
+                // The size in the event comes from stdout, which is usually captured,
+                // so ask stderr instead
+                Event::Resize(_, _) => terminal_size = terminal::size(),
+            };
+        }
+    }
+    // Leave the alternate screen before printing, in case stdout is the terminal
+    drop(guard);
+
+    if let Some(path) = selected_project {
+        select_project(&mut history, &path);
+        return Ok(());
+    }
+
+    return Err(CommandError::NothingSelected);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A list of `len` rows, `selected` and scrolled to `scroll_offset`
+    fn state(view: View, len: usize, selected_index: usize, scroll_offset: usize) -> UiState {
+        UiState {
+            query: String::new(),
+            results: vec![],
+            rows: (0..len)
+                .map(|index| Row::Project {
+                    index,
+                    depth: 0,
+                    name: index.to_string(),
+                })
+                .collect(),
+            selected_index,
+            scroll_offset,
+            view,
+            collapsed: HashSet::new(),
+            pinned: vec![],
+            columns: vec![],
+            column_cycle: 0,
+            match_mode: MatchMode::Fuzzy,
+            case_mode: CaseMode::Smart,
+            sort_mode: SortMode::default(),
+            theme: Theme::plain(),
+        }
+    }
+
+    fn scrolled(len: usize, selected: usize, offset: usize, height: usize) -> usize {
+        let mut state = state(View::Flat, len, selected, offset);
+        state.scroll_into_view(height);
+        state.scroll_offset
+    }
+
+    #[test]
+    fn scrolling_the_selection_into_view() {
+        // Already in view
+        assert_eq!(scrolled(20, 7, 5, 5), 5);
+        // Just past either edge
+        assert_eq!(scrolled(20, 10, 5, 5), 6);
+        assert_eq!(scrolled(20, 4, 5, 5), 4);
+        assert_eq!(scrolled(20, 19, 0, 5), 15);
+    }
+
+    #[test]
+    fn scrolling_short_lists() {
+        assert_eq!(scrolled(0, 0, 0, 5), 0);
+        // Shorter than the viewport, so there's nothing to scroll
+        assert_eq!(scrolled(3, 2, 0, 10), 0);
+        assert_eq!(scrolled(3, 0, 2, 10), 0);
+        // The list shrank under the offset, eg. when a group was collapsed
+        assert_eq!(scrolled(8, 7, 15, 5), 3);
+        // Nothing fits, but the offset doesn't run past the selection
+        assert_eq!(scrolled(10, 3, 0, 0), 0);
+        assert_eq!(scrolled(10, 3, 5, 0), 3);
+    }
+
+    #[test]
+    fn rows_under_the_pointer() {
+        // Seven rows of list between the header and the prompt
+        let screen = (40, 10);
+
+        let tree = state(View::Tree, 20, 0, 5);
+        assert_eq!(tree.row_at(screen, 0), None);
+        assert_eq!(tree.row_at(screen, 1), Some(5));
+        assert_eq!(tree.row_at(screen, 7), Some(11));
+        assert_eq!(tree.row_at(screen, 8), None);
+
+        // The flat list starts at the bottom
+        let flat = state(View::Flat, 20, 0, 5);
+        assert_eq!(flat.row_at(screen, 7), Some(5));
+        assert_eq!(flat.row_at(screen, 1), Some(11));
+    }
+
+    #[test]
+    fn rows_under_the_pointer_in_short_lists() {
+        let screen = (40, 10);
+
+        let flat = state(View::Flat, 3, 0, 0);
+        assert_eq!(flat.row_at(screen, 7), Some(0));
+        assert_eq!(flat.row_at(screen, 5), Some(2));
+        assert_eq!(flat.row_at(screen, 4), None);
+
+        let tree = state(View::Tree, 3, 0, 0);
+        assert_eq!(tree.row_at(screen, 3), Some(2));
+        assert_eq!(tree.row_at(screen, 4), None);
+
+        assert_eq!(state(View::Flat, 0, 0, 0).row_at(screen, 7), None);
+        // Too small to draw a list at all
+        assert_eq!(tree.row_at((10, 10), 1), None);
+        assert_eq!(tree.row_at((40, 3), 1), None);
+    }
+}
@@ -0,0 +1,198 @@
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, SetBackgroundColor, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    /// The grapheme drawn in this cell. Empty when the cell is covered by a wide
+    /// character in the cell to its left.
+    symbol: String,
+    fg: Color,
+    bg: Color,
+}
+
+fn blank() -> Cell {
+    Cell {
+        symbol: String::from(" "),
+        fg: Color::Reset,
+        bg: Color::Reset,
+    }
+}
+
+/// A double buffered view of the terminal. Each frame is drawn into the back buffer,
+/// then only the cells that changed since the last frame are written out.
+pub struct Screen {
+    cols: u16,
+    rows: u16,
+    front: Vec<Cell>,
+    back: Vec<Cell>,
+    cursor: (u16, u16),
+    /// Set when the terminal contents are unknown, eg. on the first frame or after a resize
+    needs_clear: bool,
+}
+
+impl Screen {
+    pub fn new(cols: u16, rows: u16) -> Screen {
+        let size = cols as usize * rows as usize;
+        Screen {
+            cols,
+            rows,
+            front: vec![blank(); size],
+            back: vec![blank(); size],
+            cursor: (0, 0),
+            needs_clear: true,
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.cols, self.rows)
+    }
+
+    /// Change the size of the screen. The next flush will repaint everything.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        if (cols, rows) != (self.cols, self.rows) {
+            *self = Screen::new(cols, rows);
+        }
+    }
+
+    /// Write text into the back buffer, clipped to the edge of the screen
+    pub fn print(&mut self, col: u16, row: u16, text: &str, fg: Color, bg: Color) {
+        if row >= self.rows {
+            return;
+        }
+
+        let mut x = col as usize;
+        for grapheme in text.graphemes(true) {
+            let width = UnicodeWidthStr::width(grapheme);
+            if width == 0 {
+                continue;
+            }
+            if x + width > self.cols as usize {
+                // Don't leave half of a wide character hanging off the edge
+                if x < self.cols as usize {
+                    self.set(x, row, " ", fg, bg);
+                }
+                break;
+            }
+
+            self.set(x, row, grapheme, fg, bg);
+            for covered in x + 1..x + width {
+                self.set(covered, row, "", fg, bg);
+            }
+            x += width;
+        }
+    }
+
+    fn set(&mut self, col: usize, row: u16, symbol: &str, fg: Color, bg: Color) {
+        let index = self.index(col as u16, row);
+        self.back[index] = Cell {
+            symbol: symbol.to_string(),
+            fg,
+            bg,
+        };
+    }
+
+    /// Fill the rest of a row with a background colour, eg. for highlighted rows
+    pub fn fill(&mut self, col: u16, row: u16, width: u16, bg: Color) {
+        let width = width.min(self.cols.saturating_sub(col));
+        self.print(col, row, &" ".repeat(width as usize), Color::Reset, bg);
+    }
+
+    /// Where the terminal cursor should be left after the frame is drawn
+    pub fn set_cursor(&mut self, col: u16, row: u16) {
+        self.cursor = (col.min(self.cols.saturating_sub(1)), row);
+    }
+
+    fn index(&self, col: u16, row: u16) -> usize {
+        row as usize * self.cols as usize + col as usize
+    }
+
+    /// Write out the differences between this frame and the last, then start a fresh frame
+    pub fn flush<W: Write>(&mut self, out: &mut W) -> crossterm::Result<()> {
+        if self.needs_clear {
+            queue!(out, Clear(ClearType::All))?;
+            self.front = vec![blank(); self.back.len()];
+            self.needs_clear = false;
+        }
+
+        let blank = blank();
+        let mut colors = None;
+        for row in 0..self.rows {
+            let start = self.index(0, row);
+            let line = &self.back[start..start + self.cols as usize];
+            let previous = &self.front[start..start + self.cols as usize];
+            if line == previous {
+                continue;
+            }
+
+            // Anything after the last visible cell can be cleared in one go
+            let end = line.iter().rposition(|c| *c != blank).map_or(0, |i| i + 1);
+
+            let mut col = 0;
+            while col < end {
+                if line[col] == previous[col] {
+                    col += 1;
+                    continue;
+                }
+
+                // Gather a run of changed cells that share the same colours
+                let cell = &line[col];
+                let mut run = String::new();
+                let run_start = col;
+                while col < end
+                    && line[col] != previous[col]
+                    && (line[col].fg, line[col].bg) == (cell.fg, cell.bg)
+                {
+                    run.push_str(&line[col].symbol);
+                    col += 1;
+                }
+
+                if colors != Some((cell.fg, cell.bg)) {
+                    queue!(
+                        out,
+                        SetForegroundColor(cell.fg),
+                        SetBackgroundColor(cell.bg)
+                    )?;
+                    colors = Some((cell.fg, cell.bg));
+                }
+                queue!(out, cursor::MoveTo(run_start as u16, row), Print(run))?;
+            }
+
+            if previous[end..].iter().any(|c| *c != blank) {
+                if colors != Some((Color::Reset, Color::Reset)) {
+                    queue!(
+                        out,
+                        SetForegroundColor(Color::Reset),
+                        SetBackgroundColor(Color::Reset)
+                    )?;
+                    colors = Some((Color::Reset, Color::Reset));
+                }
+                queue!(
+                    out,
+                    cursor::MoveTo(end as u16, row),
+                    Clear(ClearType::UntilNewLine)
+                )?;
+            }
+        }
+
+        queue!(
+            out,
+            SetForegroundColor(Color::Reset),
+            SetBackgroundColor(Color::Reset),
+            cursor::MoveTo(self.cursor.0, self.cursor.1)
+        )?;
+        out.flush()?;
+
+        std::mem::swap(&mut self.front, &mut self.back);
+        for cell in self.back.iter_mut() {
+            *cell = blank.clone();
+        }
+
+        Ok(())
+    }
+}
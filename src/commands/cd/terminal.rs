@@ -0,0 +1,68 @@
+use crossterm::{cursor, event, execute, terminal};
+use std::io::{stderr, IsTerminal, Write};
+use std::panic;
+use std::sync::Once;
+use term_size::dimensions_stderr;
+
+/// Size to assume when the terminal won't tell us
+const FALLBACK_SIZE: (u16, u16) = (80, 24);
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Whether the picker can be drawn. The UI goes to stderr so that stdout can be captured.
+pub fn is_interactive() -> bool {
+    stderr().is_terminal()
+}
+
+pub fn size() -> (u16, u16) {
+    match dimensions_stderr() {
+        Some((cols, rows)) => (cols as u16, rows as u16),
+        None => FALLBACK_SIZE,
+    }
+}
+
+fn restore() {
+    // Nothing useful can be done if this fails, we're already on the way out
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(
+        stderr(),
+        event::DisableMouseCapture,
+        terminal::LeaveAlternateScreen,
+        cursor::Show
+    );
+}
+
+/// Puts the terminal into raw mode on the alternate screen with the mouse captured,
+/// and puts it back the way it was when dropped, whether that's from returning early
+/// with an error or a panic.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> crossterm::Result<TerminalGuard> {
+        // The default hook prints the panic message, which would be lost on the
+        // alternate screen, so the terminal has to be restored before it runs
+        PANIC_HOOK.call_once(|| {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                default_hook(info);
+            }));
+        });
+
+        execute!(
+            stderr(),
+            terminal::EnterAlternateScreen,
+            event::EnableMouseCapture
+        )?;
+        let guard = TerminalGuard;
+        terminal::enable_raw_mode()?;
+
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
@@ -0,0 +1,111 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Number of terminal columns the text takes up
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Take graphemes from an iterator until they'd no longer fit in `max_width` columns
+fn take_width<'a>(graphemes: impl Iterator<Item = &'a str>, max_width: usize) -> Vec<&'a str> {
+    let mut width = 0;
+    graphemes
+        .take_while(|g| {
+            width += display_width(g);
+            width <= max_width
+        })
+        .collect()
+}
+
+/// Cut the end off text that's wider than `max_width` columns, marking it with `..`
+pub fn truncate_end(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width < 2 {
+        return take_width(s.graphemes(true), max_width).concat();
+    }
+
+    take_width(s.graphemes(true), max_width - 2).concat() + ".."
+}
+
+/// Cut the beginning off text that's wider than `max_width` columns, marking it with `..`
+pub fn truncate_beginning(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width < 2 {
+        let mut kept = take_width(s.graphemes(true).rev(), max_width);
+        kept.reverse();
+        return kept.concat();
+    }
+
+    let mut kept = take_width(s.graphemes(true).rev(), max_width - 2);
+    kept.reverse();
+    String::from("..") + &kept.concat()
+}
+
+/// Pad or truncate text to exactly `width` columns
+pub fn fit_width(s: &str, width: usize) -> String {
+    let truncated = truncate_end(s, width);
+    let padding = width.saturating_sub(display_width(&truncated));
+    truncated + &" ".repeat(padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncating_the_end() {
+        assert_eq!(truncate_end("hello", 5), "hello");
+        assert_eq!(truncate_end("hello world", 7), "hello..");
+        // A wide character that would straddle the cut is left out
+        assert_eq!(truncate_end("日本語", 5), "日..");
+        assert_eq!(truncate_end("日本語", 4), "日..");
+        // Combining marks stay with the letter they're on
+        assert_eq!(truncate_end("e\u{301}e\u{301}xyz", 4), "e\u{301}e\u{301}..");
+        assert_eq!(
+            truncate_end("e\u{301}e\u{301}xyz", 5),
+            "e\u{301}e\u{301}xyz"
+        );
+    }
+
+    #[test]
+    fn truncating_the_beginning() {
+        assert_eq!(truncate_beginning("hello", 5), "hello");
+        assert_eq!(truncate_beginning("hello world", 7), "..world");
+        assert_eq!(truncate_beginning("日本語", 5), "..語");
+        assert_eq!(
+            truncate_beginning("xyze\u{301}e\u{301}", 4),
+            "..e\u{301}e\u{301}"
+        );
+    }
+
+    #[test]
+    fn truncating_to_almost_nothing() {
+        for truncate in &[truncate_end, truncate_beginning] {
+            assert_eq!(truncate("abc", 0), "");
+            assert_eq!(truncate("abc", 2), "..");
+            assert_eq!(truncate("日本", 0), "");
+            assert_eq!(truncate("日本", 1), "");
+            assert_eq!(truncate("日本", 2), "..");
+        }
+        assert_eq!(truncate_end("abc", 1), "a");
+        assert_eq!(truncate_beginning("abc", 1), "c");
+    }
+
+    #[test]
+    fn fitting_to_a_width() {
+        assert_eq!(fit_width("ab", 4), "ab  ");
+        assert_eq!(fit_width("hello", 4), "he..");
+        // Padded out where a wide character didn't fit
+        assert_eq!(fit_width("日本語", 5), "日.. ");
+        assert_eq!(fit_width("日", 1), " ");
+        assert_eq!(fit_width("e\u{301}", 2), "e\u{301} ");
+        assert_eq!(fit_width("abc", 0), "");
+        for width in 0..8 {
+            assert_eq!(display_width(&fit_width("日本語", width)), width);
+        }
+    }
+}
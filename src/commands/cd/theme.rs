@@ -0,0 +1,237 @@
+use crossterm::style::Color;
+use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::env;
+
+/// Whether to draw the picker in colour
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Colour unless the `NO_COLOR` environment variable is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub const NAMES: &'static [&'static str] = &["auto", "always", "never"];
+
+    pub fn from_name(name: &str) -> Option<ColorChoice> {
+        match name {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // https://no-color.org/
+            ColorChoice::Auto => env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// For terminals with a dark background
+    #[default]
+    Dark,
+    /// For terminals with a light background
+    Light,
+}
+
+/// A colour in the config file. Either a name like `red` or `dark-blue`, `default` for
+/// the terminal's own colour, a number from the 256 colour palette, or `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ThemeColor(Color);
+
+const COLOR_NAMES: &[(&str, Color)] = &[
+    ("default", Color::Reset),
+    ("black", Color::Black),
+    ("dark-grey", Color::DarkGrey),
+    ("red", Color::Red),
+    ("dark-red", Color::DarkRed),
+    ("green", Color::Green),
+    ("dark-green", Color::DarkGreen),
+    ("yellow", Color::Yellow),
+    ("dark-yellow", Color::DarkYellow),
+    ("blue", Color::Blue),
+    ("dark-blue", Color::DarkBlue),
+    ("magenta", Color::Magenta),
+    ("dark-magenta", Color::DarkMagenta),
+    ("cyan", Color::Cyan),
+    ("dark-cyan", Color::DarkCyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+];
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<ThemeColor, String> {
+        if let Some((_, color)) = COLOR_NAMES.iter().find(|(n, _)| *n == name) {
+            return Ok(ThemeColor(*color));
+        }
+        if let Ok(value) = name.parse::<u8>() {
+            return Ok(ThemeColor(Color::AnsiValue(value)));
+        }
+        if name.len() == 7 && name.starts_with('#') {
+            let channel = |i: usize| u8::from_str_radix(&name[i..i + 2], 16);
+            if let (Ok(r), Ok(g), Ok(b)) = (channel(1), channel(3), channel(5)) {
+                return Ok(ThemeColor(Color::Rgb { r, g, b }));
+            }
+        }
+        Err(format!("unknown colour `{}`", name))
+    }
+}
+
+impl From<ThemeColor> for String {
+    fn from(color: ThemeColor) -> String {
+        match color.0 {
+            Color::AnsiValue(value) => value.to_string(),
+            Color::Rgb { r, g, b } => format!("#{:02x}{:02x}{:02x}", r, g, b),
+            named => COLOR_NAMES
+                .iter()
+                .find(|(_, c)| *c == named)
+                .map_or("default", |(n, _)| n)
+                .to_string(),
+        }
+    }
+}
+
+/// The `[theme]` section of the config. Starts from a preset, and any colour that's
+/// set replaces the preset's.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub preset: Preset,
+    /// Column titles along the top
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_fg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_bg: Option<ThemeColor>,
+    /// Projects that aren't selected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_fg: Option<ThemeColor>,
+    /// The selected row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_fg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_bg: Option<ThemeColor>,
+    /// Projects with uncommitted changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dirty_fg: Option<ThemeColor>,
+    /// Host and owner headings in the tree view
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_fg: Option<ThemeColor>,
+    /// Result count and modes under the list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer_fg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_fg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_bg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scrollbar_fg: Option<ThemeColor>,
+}
+
+/// Colours for each part of the picker
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub heading_fg: Color,
+    pub heading_bg: Color,
+    pub text_fg: Color,
+    pub highlight_fg: Color,
+    pub highlight_bg: Color,
+    pub dirty_fg: Color,
+    pub group_fg: Color,
+    pub footer_fg: Color,
+    pub prompt_fg: Color,
+    pub prompt_bg: Color,
+    pub scrollbar_fg: Color,
+}
+
+impl Theme {
+    // https://jonasjacek.github.io/colors/
+    pub fn dark() -> Theme {
+        Theme {
+            heading_fg: Color::Black,
+            heading_bg: Color::White,
+            text_fg: Color::Reset,
+            highlight_fg: Color::Black,
+            highlight_bg: Color::White,
+            dirty_fg: Color::Red,
+            group_fg: Color::AnsiValue(75),
+            footer_fg: Color::AnsiValue(219),
+            prompt_fg: Color::Blue,
+            prompt_bg: Color::White,
+            scrollbar_fg: Color::AnsiValue(240),
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            heading_fg: Color::White,
+            heading_bg: Color::AnsiValue(238),
+            text_fg: Color::Reset,
+            highlight_fg: Color::Black,
+            highlight_bg: Color::AnsiValue(153),
+            dirty_fg: Color::AnsiValue(160),
+            group_fg: Color::AnsiValue(25),
+            footer_fg: Color::AnsiValue(127),
+            prompt_fg: Color::AnsiValue(19),
+            prompt_bg: Color::AnsiValue(254),
+            scrollbar_fg: Color::AnsiValue(248),
+        }
+    }
+
+    /// The terminal's own colours everywhere. The selection is still marked with `>`.
+    pub fn plain() -> Theme {
+        Theme {
+            heading_fg: Color::Reset,
+            heading_bg: Color::Reset,
+            text_fg: Color::Reset,
+            highlight_fg: Color::Reset,
+            highlight_bg: Color::Reset,
+            dirty_fg: Color::Reset,
+            group_fg: Color::Reset,
+            footer_fg: Color::Reset,
+            prompt_fg: Color::Reset,
+            prompt_bg: Color::Reset,
+            scrollbar_fg: Color::Reset,
+        }
+    }
+
+    pub fn from_config(config: &ThemeConfig, color: ColorChoice) -> Theme {
+        if !color.enabled() {
+            return Theme::plain();
+        }
+
+        let preset = match config.preset {
+            Preset::Dark => Theme::dark(),
+            Preset::Light => Theme::light(),
+        };
+        let pick = |custom: Option<ThemeColor>, default: Color| custom.map_or(default, |c| c.0);
+
+        Theme {
+            heading_fg: pick(config.heading_fg, preset.heading_fg),
+            heading_bg: pick(config.heading_bg, preset.heading_bg),
+            text_fg: pick(config.text_fg, preset.text_fg),
+            highlight_fg: pick(config.highlight_fg, preset.highlight_fg),
+            highlight_bg: pick(config.highlight_bg, preset.highlight_bg),
+            dirty_fg: pick(config.dirty_fg, preset.dirty_fg),
+            group_fg: pick(config.group_fg, preset.group_fg),
+            footer_fg: pick(config.footer_fg, preset.footer_fg),
+            prompt_fg: pick(config.prompt_fg, preset.prompt_fg),
+            prompt_bg: pick(config.prompt_bg, preset.prompt_bg),
+            scrollbar_fg: pick(config.scrollbar_fg, preset.scrollbar_fg),
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use crate::matching::MatchResult;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    /// Every match in a single list
+    #[default]
+    Flat,
+    /// Projects grouped under their host and owner
+    Tree,
+}
+
+impl View {
+    pub const NAMES: &'static [&'static str] = &["flat", "tree"];
+
+    pub fn from_name(name: &str) -> Option<View> {
+        match name {
+            "flat" => Some(View::Flat),
+            "tree" => Some(View::Tree),
+            _ => None,
+        }
+    }
+}
+
+/// A heading for a host (eg. `github.com`) or an owner (eg. `github.com/jordwest`)
+#[derive(Debug)]
+pub struct Group {
+    /// Path of the group from the root, used to remember whether it's collapsed
+    pub key: String,
+    pub name: String,
+    pub depth: usize,
+    pub count: usize,
+    pub dirty_count: usize,
+    pub expanded: bool,
+}
+
+/// A line in the project list
+#[derive(Debug)]
+pub enum Row {
+    Group(Group),
+    Project {
+        /// Index into the match results
+        index: usize,
+        depth: usize,
+        /// What's left of the path once the group names are taken off
+        name: String,
+    },
+}
+
+impl Row {
+    pub fn depth(&self) -> usize {
+        match self {
+            Row::Group(group) => group.depth,
+            Row::Project { depth, .. } => *depth,
+        }
+    }
+}
+
+/// Key of the group holding the pinned projects. Can't clash with a host, which
+/// never starts with `:`.
+pub const PINNED_KEY: &str = ":pinned";
+
+/// One row per result, in order. Without a query (`unfiltered`), the pinned projects
+/// are taken out and listed first under their own heading, and worktrees and
+/// submodules follow their repository, one level further in.
+pub fn flat_rows(
+    results: &[MatchResult],
+    collapsed: &HashSet<String>,
+    unfiltered: bool,
+) -> Vec<Row> {
+    let (pinned, rest) = split_pinned(results, unfiltered);
+    // While searching, every row stays where its score puts it
+    let children = match unfiltered {
+        true => children_by_parent(results, &rest),
+        false => HashMap::new(),
+    };
+    let nested: HashSet<usize> = children.values().flatten().copied().collect();
+
+    let mut rows = vec![];
+    if !pinned.is_empty() {
+        let heading = group_row(
+            PINNED_KEY,
+            "pinned",
+            0,
+            pinned.iter().copied(),
+            results,
+            collapsed,
+        );
+        if !collapsed.contains(PINNED_KEY) {
+            for index in pinned {
+                push_project_bottom_up(&mut rows, results, &children, index);
+            }
+        }
+        // The flat list is drawn from the bottom up, so the heading goes after the
+        // pinned projects to be drawn above them
+        rows.push(heading);
+    }
+    for index in rest.into_iter().filter(|index| !nested.contains(index)) {
+        push_project_bottom_up(&mut rows, results, &children, index);
+    }
+
+    rows
+}
+
+/// A project's row and its worktrees and submodules, in the order the flat list draws
+/// them from the bottom up so the project ends up above them
+fn push_project_bottom_up(
+    rows: &mut Vec<Row>,
+    results: &[MatchResult],
+    children: &HashMap<usize, Vec<usize>>,
+    index: usize,
+) {
+    let start = rows.len();
+    let name = results[index].display.clone();
+    push_project(rows, results, children, (index, 0, name));
+    rows[start..].reverse();
+}
+
+/// Indexes of the pinned results and of everything else. Nothing counts as pinned
+/// without a section to show it in.
+fn split_pinned(results: &[MatchResult], pinned_section: bool) -> (Vec<usize>, Vec<usize>) {
+    (0..results.len()).partition(|index| pinned_section && results[*index].pinned)
+}
+
+type Owners<'a> = BTreeMap<Option<&'a str>, Vec<(usize, &'a str)>>;
+
+/// Worktrees and submodules of each result, by the index of the repository they belong
+/// to. Those whose repository isn't in the results stay where they are.
+fn children_by_parent(results: &[MatchResult], indexes: &[usize]) -> HashMap<usize, Vec<usize>> {
+    let by_path: HashMap<&Path, usize> = results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| (result.project.path.as_path(), index))
+        .collect();
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in indexes {
+        let result = &results[*index];
+        let parent = match (&result.project.worktree, &result.project.superproject) {
+            (Some(worktree), _) => Some(worktree.main.as_path()),
+            (None, superproject) => superproject.as_deref(),
+        };
+        if let Some(parent) = parent.and_then(|parent| by_path.get(parent)) {
+            children.entry(*parent).or_default().push(*index);
+        }
+    }
+    children
+}
+
+/// A project's row followed by its worktrees and submodules, one level further in
+fn push_project(
+    rows: &mut Vec<Row>,
+    results: &[MatchResult],
+    children: &HashMap<usize, Vec<usize>>,
+    (index, depth, name): (usize, usize, String),
+) {
+    rows.push(Row::Project { index, depth, name });
+
+    let parent = &results[index].project.path;
+    for child in children.get(&index).into_iter().flatten() {
+        // Submodules by their path in the repository, worktrees by their directory
+        let path = &results[*child].project.path;
+        let name = match path.strip_prefix(parent) {
+            Ok(relative) => relative.to_string_lossy(),
+            Err(_) => path.file_name().unwrap_or_default().to_string_lossy(),
+        };
+        let row = (*child, depth + 1, name.to_string());
+        push_project(rows, results, children, row);
+    }
+}
+
+/// Group results under their host and owner, following the `host/owner/repo` layout.
+/// Pinned projects come first in a group of their own. Projects that sit directly
+/// under a host are listed before its owners, and projects directly under the root
+/// or outside every root aren't grouped at all. Worktrees and submodules go under their
+/// repository.
+pub fn tree_rows(results: &[MatchResult], collapsed: &HashSet<String>) -> Vec<Row> {
+    let (pinned, rest) = split_pinned(results, true);
+    let children = children_by_parent(results, &rest);
+    let mut ungrouped = vec![];
+    let mut hosts: BTreeMap<&str, Owners> = BTreeMap::new();
+
+    let nested: HashSet<usize> = children.values().flatten().copied().collect();
+    for index in rest.into_iter().filter(|index| !nested.contains(index)) {
+        let result = &results[index];
+        if Path::new(&result.display).is_absolute() {
+            ungrouped.push(index);
+            continue;
+        }
+
+        let parts: Vec<&str> = result.display.splitn(3, '/').collect();
+        match parts[..] {
+            [host, owner, name] => hosts
+                .entry(host)
+                .or_default()
+                .entry(Some(owner))
+                .or_default()
+                .push((index, name)),
+            [host, name] => hosts
+                .entry(host)
+                .or_default()
+                .entry(None)
+                .or_default()
+                .push((index, name)),
+            _ => ungrouped.push(index),
+        }
+    }
+
+    let mut rows = vec![];
+    if !pinned.is_empty() {
+        rows.push(group_row(
+            PINNED_KEY,
+            "pinned",
+            0,
+            pinned.iter().copied(),
+            results,
+            collapsed,
+        ));
+        if !collapsed.contains(PINNED_KEY) {
+            for index in pinned {
+                let name = results[index].display.clone();
+                push_project(&mut rows, results, &children, (index, 1, name));
+            }
+        }
+    }
+    for index in ungrouped {
+        let name = results[index].display.clone();
+        push_project(&mut rows, results, &children, (index, 0, name));
+    }
+
+    for (host, owners) in hosts {
+        let all_projects = owners.values().flatten().map(|(index, _)| *index);
+        rows.push(group_row(host, host, 0, all_projects, results, collapsed));
+        if collapsed.contains(host) {
+            continue;
+        }
+
+        for (owner, projects) in owners {
+            let depth = match owner {
+                Some(owner) => {
+                    let key = format!("{}/{}", host, owner);
+                    let indexes = projects.iter().map(|(index, _)| *index);
+                    let expanded = !collapsed.contains(&key);
+                    rows.push(group_row(&key, owner, 1, indexes, results, collapsed));
+                    if !expanded {
+                        continue;
+                    }
+                    2
+                }
+                None => 1,
+            };
+
+            for (index, name) in projects {
+                let row = (index, depth, name.to_string());
+                push_project(&mut rows, results, &children, row);
+            }
+        }
+    }
+
+    rows
+}
+
+fn group_row(
+    key: &str,
+    name: &str,
+    depth: usize,
+    indexes: impl Iterator<Item = usize>,
+    results: &[MatchResult],
+    collapsed: &HashSet<String>,
+) -> Row {
+    let mut count = 0;
+    let mut dirty_count = 0;
+    for index in indexes {
+        count += 1;
+        if results[index].dirty {
+            dirty_count += 1;
+        }
+    }
+
+    Row::Group(Group {
+        key: key.to_string(),
+        name: name.to_string(),
+        depth,
+        count,
+        dirty_count,
+        expanded: !collapsed.contains(key),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::cache::Project;
+    use crate::discovery::vcs::git::Worktree;
+    use std::path::PathBuf;
+
+    fn result(path: &str, main: Option<&str>, superproject: Option<&str>) -> MatchResult {
+        MatchResult {
+            project: Project {
+                path: PathBuf::from(path),
+                root: PathBuf::from("/src"),
+                tags: vec![],
+                vcs: None,
+                project_type: None,
+                worktree: main.map(|main| Worktree {
+                    main: PathBuf::from(main),
+                    branch: None,
+                }),
+                superproject: superproject.map(PathBuf::from),
+            },
+            display: path.trim_start_matches("/src/").to_string(),
+            score: 0,
+            root_index: 0,
+            dirty: false,
+            last_commit_time: None,
+            last_visited: None,
+            alias: None,
+            pinned: false,
+        }
+    }
+
+    fn projects(rows: &[Row]) -> Vec<(usize, usize, &str)> {
+        rows.iter()
+            .filter_map(|row| match row {
+                Row::Project { index, depth, name } => Some((*index, *depth, name.as_str())),
+                Row::Group(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flat_rows_nest_worktrees_and_submodules_without_a_query() {
+        let results = [
+            result("/src/repo-feature", Some("/src/repo"), None),
+            result("/src/other", None, None),
+            result("/src/repo", None, None),
+            result("/src/repo/lib", None, Some("/src/repo")),
+        ];
+
+        // Drawn from the bottom up, so the repository ends up above what's under it
+        let rows = flat_rows(&results, &HashSet::new(), true);
+        assert_eq!(
+            projects(&rows),
+            vec![
+                (1, 0, "other"),
+                (3, 1, "lib"),
+                (0, 1, "repo-feature"),
+                (2, 0, "repo"),
+            ]
+        );
+
+        let rows = flat_rows(&results, &HashSet::new(), false);
+        let depths: Vec<_> = projects(&rows).iter().map(|row| (row.0, row.1)).collect();
+        assert_eq!(depths, vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+    }
+}
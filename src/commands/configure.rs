@@ -63,8 +63,10 @@ pub fn configure() -> Result<(), ConfigureError> {
         None => home_dir.join(".prj"),
     };
 
-    let root = PathBuf::from(root);
-    let config = Config { root, location };
+    // Keep any other settings from the existing config
+    let mut config = existing_config.unwrap_or_default();
+    config.root = PathBuf::from(root);
+    config.location = location;
 
     config
         .write_config()
@@ -0,0 +1,229 @@
+use crate::config::Config;
+use crate::daemon::{socket_path, Entry, ErrorResponse, Request, Skipped, Snapshot};
+use crate::discovery::cache::{Cache, CacheClient, Project};
+use crate::discovery::scan_roots;
+use crate::discovery::size::directory_size;
+use crate::discovery::vcs::fetch_project_info;
+use crate::discovery::vcs::git::linked_git_dir;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// How long to wait between searches. With the directory cache, a search of a tree
+/// that hasn't changed is cheap, and only the projects that look like they've changed
+/// have their status fetched again.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the status and size of every project is fetched again, changed or not.
+/// Editing a file deep inside a project doesn't show at the top of it.
+const FULL_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How long a connection can go without sending a request before it's closed
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Modification times that move when a project changes in a way its details show
+type Stamp = Vec<Option<SystemTime>>;
+
+/// When entries are added or removed at the top of the project, or its repository
+/// moves to another commit, stages something or checks something out, which rewrite
+/// files in the repository's directory
+fn stamp(project: &Project) -> Stamp {
+    let mut watched = vec![project.path.clone()];
+    if let Some(vcs) = project.vcs {
+        watched.extend(vcs.markers().map(|marker| project.path.join(marker)));
+    }
+    // A worktree's or a submodule's repository is somewhere else
+    watched.extend(linked_git_dir(&project.path));
+
+    watched
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum DaemonError {
+    HomeDirNotFound,
+    /// Another daemon is already listening on the socket
+    AlreadyRunning(PathBuf),
+    FailedToListen(io::Error),
+    /// There are no Unix sockets on this platform
+    #[cfg(not(unix))]
+    Unsupported,
+}
+
+/// Everything the daemon knows, shared by the thread that keeps it up to date and the
+/// ones answering requests
+struct Daemon {
+    config: Mutex<Config>,
+    cache: CacheClient,
+    /// Held while searching, so only one search runs at a time
+    scanning: Mutex<()>,
+    skipped: Mutex<Vec<Skipped>>,
+}
+
+impl Daemon {
+    /// Search the roots again, reading only the directories that have changed unless
+    /// `full_rescan`. The config is read again first, in case it's been edited.
+    fn scan(&self, full_rescan: bool) {
+        let _scanning = self.scanning.lock().unwrap();
+        if let Ok(config) = Config::autoload() {
+            *self.config.lock().unwrap() = config;
+        }
+        let config = self.config.lock().unwrap().clone();
+
+        let found = Mutex::new(vec![]);
+        let skipped = scan_roots(&config, full_rescan, |project| {
+            found.lock().unwrap().push(project)
+        });
+        self.cache.set_projects(found.into_inner().unwrap());
+        *self.skipped.lock().unwrap() = skipped
+            .into_iter()
+            .map(|(path, reason)| Skipped {
+                path,
+                reason: reason.to_string(),
+            })
+            .collect();
+    }
+
+    /// Fetch the status of the repositories that are new or have changed since `stamps`
+    /// was filled, or of `everything`, and their sizes too if the config shows them
+    fn refresh_details(&self, stamps: &mut HashMap<PathBuf, Stamp>, everything: bool) {
+        let mut cache = self.cache.clone();
+        let measure_sizes = self.config.lock().unwrap().shows_sizes();
+
+        let mut refreshed = HashMap::new();
+        for p in cache.get_projects() {
+            // Taken first, so a change while fetching is picked up next time
+            let stamp = stamp(&p);
+            if everything || stamps.get(&p.path) != Some(&stamp) {
+                if let Some(vcs_info) = fetch_project_info(&p) {
+                    cache.add_vcs_info(&p.path, vcs_info);
+                }
+                if measure_sizes {
+                    cache.add_size(&p.path, directory_size(&p.path));
+                }
+            }
+            refreshed.insert(p.path, stamp);
+        }
+        // Forget the projects that have gone
+        *stamps = refreshed;
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let projects = self
+            .cache
+            .get_projects()
+            .into_iter()
+            .map(|project| Entry {
+                vcs_info: self.cache.get_vcs_info(&project.path),
+                size: self.cache.get_size(&project.path),
+                project,
+            })
+            .collect();
+
+        Snapshot {
+            projects,
+            skipped: self.skipped.lock().unwrap().clone(),
+        }
+    }
+
+    fn answer(&self, request: Request) -> Snapshot {
+        match request {
+            Request::Projects { full_rescan } => {
+                if full_rescan {
+                    self.scan(true);
+                }
+                self.snapshot()
+            }
+        }
+    }
+}
+
+/// Keep the projects up to date in the background, and answer requests for them on
+/// a Unix socket until killed
+#[cfg(unix)]
+pub fn run(config: Config) -> Result<(), DaemonError> {
+    use std::io::{BufRead, BufReader, BufWriter, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Instant;
+
+    let socket = socket_path().ok_or(DaemonError::HomeDirNotFound)?;
+    if UnixStream::connect(&socket).is_ok() {
+        return Err(DaemonError::AlreadyRunning(socket));
+    }
+    // Nothing's listening, so it was left behind by a daemon that was killed
+    let _ = std::fs::remove_file(&socket);
+
+    let daemon = Arc::new(Daemon {
+        config: Mutex::new(config),
+        cache: Cache::new().share(),
+        scanning: Mutex::new(()),
+        skipped: Mutex::new(vec![]),
+    });
+
+    // Search before listening, so nobody is given an empty list. Until then they
+    // search for themselves.
+    daemon.scan(false);
+    let listener = UnixListener::bind(&socket).map_err(DaemonError::FailedToListen)?;
+
+    let updater = daemon.clone();
+    thread::spawn(move || {
+        let mut stamps = HashMap::new();
+        let mut refreshed_everything: Option<Instant> = None;
+        loop {
+            let everything =
+                refreshed_everything.is_none_or(|at| at.elapsed() >= FULL_REFRESH_INTERVAL);
+            if everything {
+                refreshed_everything = Some(Instant::now());
+            }
+            updater.refresh_details(&mut stamps, everything);
+            thread::sleep(RESCAN_INTERVAL);
+            updater.scan(false);
+        }
+    });
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        // A client that stops reading or writing would keep its thread forever
+        if stream.set_read_timeout(Some(IDLE_TIMEOUT)).is_err()
+            || stream.set_write_timeout(Some(IDLE_TIMEOUT)).is_err()
+        {
+            continue;
+        }
+        let daemon = daemon.clone();
+        thread::spawn(move || {
+            let mut writer = BufWriter::new(&stream);
+            for line in BufReader::new(&stream).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => return,
+                };
+                let written = match serde_json::from_str(&line) {
+                    Ok(request) => serde_json::to_writer(&mut writer, &daemon.answer(request)),
+                    Err(e) => {
+                        let error = e.to_string();
+                        serde_json::to_writer(&mut writer, &ErrorResponse { error })
+                    }
+                };
+                if written.is_err() || writeln!(writer).and_then(|_| writer.flush()).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn run(_config: Config) -> Result<(), DaemonError> {
+    Err(DaemonError::Unsupported)
+}
@@ -0,0 +1,49 @@
+use super::print_path;
+use crate::config::Config;
+use crate::daemon::client::fetch_projects;
+use crate::discovery::find_projects;
+use std::path::PathBuf;
+
+/// Print the path of every project, one per line. Useful in scripts, and anywhere
+/// there's no terminal to draw the picker on. With `diagnostics`, the directories that
+/// couldn't be searched are listed on stderr afterwards. With `full_rescan`, every
+/// directory is read even if it hasn't changed since the last search.
+pub fn run(config: &Config, diagnostics: bool, full_rescan: bool) {
+    let (mut projects, skipped): (Vec<PathBuf>, Vec<(PathBuf, String)>) =
+        match fetch_projects(full_rescan) {
+            Some(snapshot) => (
+                snapshot
+                    .projects
+                    .into_iter()
+                    .map(|entry| entry.project.path)
+                    .collect(),
+                snapshot
+                    .skipped
+                    .into_iter()
+                    .map(|skipped| (skipped.path, skipped.reason))
+                    .collect(),
+            ),
+            None => {
+                let (projects, skipped) = find_projects(config, full_rescan);
+                (
+                    projects.into_iter().map(|project| project.path).collect(),
+                    skipped
+                        .into_iter()
+                        .map(|(path, reason)| (path, reason.to_string()))
+                        .collect(),
+                )
+            }
+        };
+    projects.sort();
+
+    for path in projects {
+        print_path(&path);
+    }
+
+    if diagnostics {
+        for (path, reason) in &skipped {
+            eprintln!("skipped {}: {}", path.display(), reason);
+        }
+        eprintln!("{} skipped", skipped.len());
+    }
+}
@@ -1,2 +1,29 @@
+pub mod alias;
 pub mod cd;
 pub mod configure;
+pub mod daemon;
+pub mod ls;
+pub mod path;
+pub mod pin;
+pub mod tag;
+
+use std::io::{stdout, Write};
+use std::path::Path;
+
+/// Print a path on a line of its own for the shell or a script to use. On Unix the
+/// bytes are written as they are, so names that aren't UTF-8 come through unchanged.
+pub fn print_path(path: &Path) {
+    #[cfg(unix)]
+    let bytes = {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes()
+    };
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy();
+    #[cfg(not(unix))]
+    let bytes = bytes.as_bytes();
+
+    let mut stdout = stdout();
+    let _ = stdout.write_all(bytes);
+    let _ = stdout.write_all(b"\n");
+}
@@ -0,0 +1,94 @@
+use crate::commands::cd::display_path;
+use crate::config::Config;
+use crate::daemon::client::fetch_projects;
+use crate::discovery::find_projects;
+use crate::history::History;
+use crate::matching::Matcher;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+/// Most candidates to suggest when a name is ambiguous
+const MAX_SUGGESTIONS: usize = 5;
+
+pub enum PathError {
+    NoMatch,
+    /// Several projects match equally well, best first
+    Ambiguous(Vec<String>),
+}
+
+struct Candidate {
+    path: PathBuf,
+    display: String,
+    /// Whether the query is the project's whole name or path
+    exact: bool,
+    score: i64,
+    frecency: f64,
+}
+
+impl Candidate {
+    /// Better candidates sort first: exact names, then by match score, then by how
+    /// often and recently they've been visited
+    fn rank(&self, other: &Candidate) -> Ordering {
+        other
+            .exact
+            .cmp(&self.exact)
+            .then(other.score.cmp(&self.score))
+            .then(other.frecency.total_cmp(&self.frecency))
+            .then_with(|| self.display.cmp(&other.display))
+    }
+
+    fn ties_with(&self, other: &Candidate) -> bool {
+        self.exact == other.exact && self.score == other.score && self.frecency == other.frecency
+    }
+}
+
+/// Find the single project that best matches `query`, without drawing anything.
+/// An alias with exactly that name wins outright. With `full_rescan`, every directory
+/// is read even if it hasn't changed since the last search.
+pub fn resolve(config: &Config, query: &str, full_rescan: bool) -> Result<PathBuf, PathError> {
+    if let Some(path) = config.aliases.get(query) {
+        return Ok(config.found_path(path));
+    }
+
+    let roots = config.all_roots();
+    let show_root_labels = roots.len() > 1;
+    let history = History::autoload();
+    let matcher = Matcher::new(query, config.match_mode, config.case_mode);
+
+    let projects = match fetch_projects(full_rescan) {
+        Some(snapshot) => snapshot.projects.into_iter().map(|e| e.project).collect(),
+        None => find_projects(config, full_rescan).0,
+    };
+
+    let mut candidates: Vec<Candidate> = vec![];
+    for project in projects {
+        let display = display_path(&project, show_root_labels);
+        let alias = config.alias_for(&project.path);
+        let tags = config.project_tags(&project);
+
+        if let Some(score) = matcher.score_project(&display, alias, &tags) {
+            let name = project.path.file_name().map(|n| n.to_string_lossy());
+            candidates.push(Candidate {
+                exact: display == query || name.is_some_and(|n| n == query),
+                frecency: history.frecency(&project.path),
+                path: project.path,
+                display,
+                score,
+            });
+        }
+    }
+    candidates.sort_by(Candidate::rank);
+
+    match &candidates[..] {
+        [] => Err(PathError::NoMatch),
+        [best, next, ..] if best.ties_with(next) => Err(PathError::Ambiguous(
+            candidates
+                .iter()
+                .take_while(|c| c.ties_with(best))
+                .take(MAX_SUGGESTIONS)
+                .map(|c| c.display.clone())
+                .collect(),
+        )),
+        [best, ..] => Ok(best.path.clone()),
+    }
+}
@@ -0,0 +1,37 @@
+use crate::commands::alias::resolve_directory;
+use crate::config::{Config, WriteError};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum PinError {
+    NotPinned,
+    /// The path doesn't exist or isn't a directory
+    NotADirectory(PathBuf),
+    WriteError(WriteError),
+}
+
+/// Pin a directory, the current one if no path is given
+pub fn pin(config: &mut Config, path: Option<&Path>) -> Result<PathBuf, PinError> {
+    let path = resolve_directory(path).map_err(PinError::NotADirectory)?;
+
+    if !config.pinned.contains(&path) {
+        config.pinned.push(path.clone());
+        config.write_config().map_err(PinError::WriteError)?;
+    }
+
+    Ok(path)
+}
+
+pub fn unpin(config: &mut Config, path: Option<&Path>) -> Result<PathBuf, PinError> {
+    let path = resolve_directory(path).map_err(PinError::NotADirectory)?;
+
+    let position = config
+        .pinned
+        .iter()
+        .position(|p| *p == path)
+        .ok_or(PinError::NotPinned)?;
+    config.pinned.remove(position);
+    config.write_config().map_err(PinError::WriteError)?;
+
+    Ok(path)
+}
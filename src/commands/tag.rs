@@ -0,0 +1,101 @@
+use crate::commands::alias::resolve_directory;
+use crate::config::{Config, WriteError};
+use crate::discovery::find_projects;
+use crate::discovery::marker::{read_marker, MARKER_FILE};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum TagError {
+    NotTagged,
+    /// The tag comes from the project's `.prj.toml` at this path, so it has to be removed there
+    TaggedByMarker(PathBuf),
+    /// The path doesn't exist or isn't a directory
+    NotADirectory(PathBuf),
+    WriteError(WriteError),
+}
+
+/// Tag a directory, the current one if no path is given
+pub fn add(config: &mut Config, tag: &str, path: Option<&Path>) -> Result<PathBuf, TagError> {
+    let path = resolve_directory(path).map_err(TagError::NotADirectory)?;
+
+    let tags = config.tags.entry(path.clone()).or_default();
+    if !tags.iter().any(|t| t == tag) {
+        tags.push(tag.to_string());
+        config.write_config().map_err(TagError::WriteError)?;
+    }
+
+    Ok(path)
+}
+
+pub fn remove(config: &mut Config, tag: &str, path: Option<&Path>) -> Result<PathBuf, TagError> {
+    let path = resolve_directory(path).map_err(TagError::NotADirectory)?;
+
+    let tags = config.tags.get_mut(&path);
+    let position = tags
+        .as_ref()
+        .and_then(|tags| tags.iter().position(|t| t == tag));
+    match (tags, position) {
+        (Some(tags), Some(position)) => {
+            tags.remove(position);
+            if tags.is_empty() {
+                config.tags.remove(&path);
+            }
+        }
+        _ if read_marker(&path).tags.iter().any(|t| t == tag) => {
+            return Err(TagError::TaggedByMarker(path.join(MARKER_FILE)))
+        }
+        _ => return Err(TagError::NotTagged),
+    }
+    config.write_config().map_err(TagError::WriteError)?;
+
+    Ok(path)
+}
+
+/// Print the tags on a directory, or with no path, every tag and the projects that
+/// have it. Finding the tags in `.prj.toml` files means searching the roots, and the
+/// aliased and pinned directories.
+pub fn list(config: &Config, path: Option<&Path>) -> Result<(), TagError> {
+    if path.is_some() {
+        let path = resolve_directory(path).map_err(TagError::NotADirectory)?;
+        let mut tags = read_marker(&path).tags;
+        tags.extend(config.tags.get(&path).into_iter().flatten().cloned());
+        tags.sort();
+        tags.dedup();
+
+        for tag in tags {
+            println!("{}", tag);
+        }
+        return Ok(());
+    }
+
+    let mut projects_by_tag: BTreeMap<&str, Vec<PathBuf>> = BTreeMap::new();
+    for (path, tags) in &config.tags {
+        for tag in tags {
+            projects_by_tag
+                .entry(tag)
+                .or_default()
+                .push(config.found_path(path));
+        }
+    }
+    let (projects, _) = find_projects(config, false);
+    for project in &projects {
+        for tag in &project.tags {
+            projects_by_tag
+                .entry(tag)
+                .or_default()
+                .push(project.path.clone());
+        }
+    }
+
+    for (tag, mut paths) in projects_by_tag {
+        paths.sort();
+        paths.dedup();
+
+        println!("{}", tag);
+        for path in paths {
+            println!("  {}", path.display());
+        }
+    }
+    Ok(())
+}
@@ -1,21 +1,118 @@
+use crate::commands::cd::columns::{ColumnConfig, ColumnKind};
+use crate::commands::cd::theme::{ColorChoice, ThemeConfig};
+use crate::commands::cd::tree::View;
+use crate::discovery::cache::Project;
+use crate::discovery::marker::read_marker;
+use crate::discovery::parallel::default_threads;
+use crate::discovery::project_file::{detect_type, ProjectFile};
+use crate::discovery::traverse::TraverseOptions;
+use crate::discovery::vcs::git::read_worktree;
+use crate::discovery::vcs::VcsKind;
+use crate::matching::sort::SortMode;
+use crate::matching::{CaseMode, MatchMode};
 use dirs::home_dir;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Config {
     /// Location of the configuration file
     #[serde(skip)]
     pub location: PathBuf,
 
     pub root: PathBuf,
+
+    /// Additional directories to search for projects, alongside `root`
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+
+    /// How the query is matched against projects: fuzzy, exact, prefix or regex
+    #[serde(default)]
+    pub match_mode: MatchMode,
+
+    /// Case sensitivity of the query: smart, insensitive or sensitive
+    #[serde(default)]
+    pub case_mode: CaseMode,
+
+    /// Order of the results: score, name, last-commit, last-visited, dirty-first or root
+    #[serde(default)]
+    pub sort: SortMode,
+
+    /// Show a flat list, or a tree grouped by host and owner while the query is empty
+    #[serde(default)]
+    pub view: View,
+
+    /// Whether to use colour: auto, always or never. Auto turns it off when `NO_COLOR` is set.
+    #[serde(default)]
+    pub color: ColorChoice,
+
+    /// Also list bare repositories, like `name.git` directories
+    #[serde(default)]
+    pub bare_repos: bool,
+
+    /// Keep looking for projects inside other projects, like vendored checkouts
+    #[serde(default)]
+    pub nested: bool,
+
+    /// List the submodules of git repositories under their superproject
+    #[serde(default)]
+    pub submodules: bool,
+
+    /// Files that make a directory a project even if it isn't a repository, like
+    /// `Cargo.toml` or `*.csproj`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<String>,
+
+    /// Look inside symlinked directories while searching the roots
+    #[serde(default)]
+    pub follow_symlinks: bool,
+
+    /// How many threads search the roots. 0 uses one per CPU.
+    #[serde(default)]
+    pub threads: usize,
+
+    /// Projects listed first when the query is empty, managed with `prj pin`.
+    /// Comes before any tables, which TOML needs to be last.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<PathBuf>,
+
+    /// Columns shown in the picker, in order, with their widths. Uses the defaults when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<ColumnConfig>,
+
+    /// Colours of the picker, starting from the dark or light preset
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// Short names for projects or any other directory, managed with `prj alias`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, PathBuf>,
+
+    /// Tags for each project, managed with `prj tag`. Projects can also tag themselves
+    /// in a `.prj.toml`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<PathBuf, Vec<String>>,
+
+    /// Roots reached through a symlink, and where they really are
+    #[serde(skip)]
+    real_roots: OnceLock<Vec<(PathBuf, PathBuf)>>,
 }
 
 use toml;
 
+/// `path` inside `base`, without a trailing separator when it's `base` itself
+fn join(base: &Path, path: &Path) -> PathBuf {
+    match path.as_os_str().is_empty() {
+        true => base.to_path_buf(),
+        false => base.join(path),
+    }
+}
+
 #[derive(Debug)]
 pub enum ReadError {
     Missing,
@@ -31,6 +128,125 @@ pub enum WriteError {
 }
 
 impl Config {
+    /// All of the roots to search for projects, starting with the main root
+    pub fn all_roots(&self) -> Vec<&Path> {
+        let mut roots = vec![self.root.as_path()];
+        roots.extend(self.roots.iter().map(|r| r.as_path()));
+        roots
+    }
+
+    /// What to look for while traversing the roots
+    pub fn traverse_options(&self) -> TraverseOptions {
+        TraverseOptions {
+            bare_repos: self.bare_repos,
+            nested: self.nested,
+            submodules: self.submodules,
+            markers: self.markers.iter().map(|m| ProjectFile::new(m)).collect(),
+            follow_symlinks: self.follow_symlinks,
+            ..TraverseOptions::default()
+        }
+    }
+
+    /// How many threads to search the roots with
+    pub fn threads(&self) -> usize {
+        match self.threads {
+            0 => default_threads(),
+            n => n,
+        }
+    }
+
+    /// Whether the size column is shown, which is the only time sizes are measured
+    pub fn shows_sizes(&self) -> bool {
+        self.columns.iter().any(|c| c.kind == ColumnKind::Size)
+    }
+
+    /// The first alias for a directory, if it has one
+    pub fn alias_for(&self, path: &Path) -> Option<&str> {
+        let path = self.stored_path(path);
+        self.aliases
+            .iter()
+            .find(|(_, target)| **target == path)
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn real_roots(&self) -> &[(PathBuf, PathBuf)] {
+        self.real_roots.get_or_init(|| {
+            self.all_roots()
+                .into_iter()
+                .filter_map(|root| Some((root.to_path_buf(), root.canonicalize().ok()?)))
+                .filter(|(root, real)| root != real)
+                .collect()
+        })
+    }
+
+    /// The path of a directory found by searching the roots, in the form aliases, pins
+    /// and tags are kept in. Those are real paths, so under a root that's a symlink the
+    /// two differ.
+    pub fn stored_path(&self, path: &Path) -> PathBuf {
+        for (root, real) in self.real_roots() {
+            if let Ok(relative) = path.strip_prefix(root) {
+                return join(real, relative);
+            }
+        }
+        path.to_path_buf()
+    }
+
+    /// Where a directory kept by an alias, pin or tag is found by searching the roots.
+    /// The opposite of `stored_path`.
+    pub fn found_path(&self, path: &Path) -> PathBuf {
+        for (root, real) in self.real_roots() {
+            if let Ok(relative) = path.strip_prefix(real) {
+                return join(root, relative);
+            }
+        }
+        path.to_path_buf()
+    }
+
+    /// Every tag on a project, from the config and its `.prj.toml`, in order
+    pub fn project_tags(&self, project: &Project) -> Vec<String> {
+        let mut tags = project.tags.clone();
+        if let Some(configured) = self.tags.get(&self.stored_path(&project.path)) {
+            tags.extend(configured.iter().cloned());
+        }
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Aliased and pinned directories as projects, so they can be found even when they
+    /// aren't repositories or are outside every root. Directories that don't exist are skipped.
+    /// They have the paths searching the roots would find them at, so a directory that was
+    /// found as well isn't listed twice.
+    pub fn extra_projects(&self) -> Vec<Project> {
+        let roots = self.all_roots();
+        let markers = self.traverse_options().markers;
+        let mut paths: Vec<PathBuf> = self
+            .aliases
+            .values()
+            .chain(&self.pinned)
+            .map(|path| self.found_path(path))
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        paths
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .map(|path| Project {
+                tags: read_marker(&path).tags,
+                vcs: VcsKind::detect(&path),
+                project_type: detect_type(&path, &markers),
+                worktree: read_worktree(&path),
+                superproject: None,
+                root: roots
+                    .iter()
+                    .find(|root| path.starts_with(root))
+                    .map_or_else(PathBuf::new, |root| root.to_path_buf()),
+                path,
+            })
+            .collect()
+    }
+
     /// Try to find the config file and load it
     pub fn autoload() -> Result<Config, ReadError> {
         let home = home_dir().ok_or(ReadError::HomeDirNotFound)?;
@@ -71,3 +287,35 @@ impl Config {
             .or_else(|e| Err(WriteError::IoError(e)))
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn maps_paths_under_a_symlinked_root() {
+        let dir = TestDir::new("config");
+        dir.dir("src/project");
+        dir.symlink("link", dir.join("src"));
+        let real = dir.path().canonicalize().unwrap().join("src");
+
+        let config = Config {
+            root: dir.join("link"),
+            ..Config::default()
+        };
+        let found = dir.join("link/project");
+        assert_eq!(config.stored_path(&found), real.join("project"));
+        assert_eq!(config.found_path(&real.join("project")), found);
+        assert_eq!(config.found_path(&real), dir.join("link"));
+        // Paths outside every root are kept as they are
+        assert_eq!(
+            config.stored_path(Path::new("/elsewhere")),
+            Path::new("/elsewhere")
+        );
+        assert_eq!(
+            config.found_path(Path::new("/elsewhere")),
+            Path::new("/elsewhere")
+        );
+    }
+}
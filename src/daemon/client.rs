@@ -0,0 +1,34 @@
+use super::Snapshot;
+
+/// How long to wait for the daemon to answer. It answers from memory, so anything
+/// this slow has gone wrong, unless it's been asked to search everything again.
+#[cfg(unix)]
+const ANSWER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Every project, from the daemon if it's running. `None` if it isn't, or it didn't
+/// answer properly, and the caller should search for itself.
+#[cfg(unix)]
+pub fn fetch_projects(full_rescan: bool) -> Option<Snapshot> {
+    use super::{socket_path, Request};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket_path()?).ok()?;
+    let timeout = match full_rescan {
+        true => None,
+        false => Some(ANSWER_TIMEOUT),
+    };
+    stream.set_read_timeout(timeout).ok()?;
+    let request = serde_json::to_string(&Request::Projects { full_rescan }).ok()?;
+    writeln!(stream, "{}", request).ok()?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// The daemon only runs where there are Unix sockets
+#[cfg(not(unix))]
+pub fn fetch_projects(_full_rescan: bool) -> Option<Snapshot> {
+    None
+}
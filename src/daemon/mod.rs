@@ -0,0 +1,147 @@
+pub mod client;
+pub mod paths;
+
+use crate::discovery::cache::{Project, VcsInfo};
+use dirs::home_dir;
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Where the daemon listens. Requests and responses are JSON objects, one to a line.
+pub fn socket_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".prj_daemon.sock"))
+}
+
+/// What can be asked of the daemon, named by the request's `command`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    /// Every project, searching every directory again first if `full_rescan`
+    Projects {
+        #[serde(default)]
+        full_rescan: bool,
+    },
+}
+
+/// The answer to a request that couldn't be understood
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// A project with everything the daemon has found out about it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub project: Project,
+    pub vcs_info: Option<VcsInfo>,
+    /// Only measured if the config shows the size column
+    pub size: Option<u64>,
+}
+
+/// A directory that couldn't be searched, and why
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Skipped {
+    #[serde(with = "paths")]
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// The daemon's answer to a `projects` request
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub projects: Vec<Entry>,
+    pub skipped: Vec<Skipped>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::vcs::git::Worktree;
+    use crate::discovery::vcs::VcsKind;
+
+    fn entry(path: PathBuf, summary: &str) -> Entry {
+        Entry {
+            project: Project {
+                root: PathBuf::from("/home/me/src"),
+                tags: vec!["work".into()],
+                vcs: Some(VcsKind::Git),
+                project_type: Some("Rust".into()),
+                worktree: Some(Worktree {
+                    main: PathBuf::from("/home/me/src/main"),
+                    branch: None,
+                }),
+                superproject: Some(path.join("..")),
+                path,
+            },
+            vcs_info: Some(VcsInfo {
+                last_commit_summary: summary.into(),
+                last_commit_time: 1704161045,
+                current_branch_name: "main".into(),
+                uncommitted_changes: 2,
+                ahead_behind: Some((1, 0)),
+                remote_url: None,
+                head_tags: vec![],
+                out_of_sync: None,
+            }),
+            size: Some(4096),
+        }
+    }
+
+    fn round_trip(entry: &Entry) -> Entry {
+        let json = serde_json::to_string(entry).unwrap();
+        assert!(!json.contains('\n'), "{}", json);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn entry_keeps_the_project_fields_at_the_top() {
+        let json = serde_json::to_value(entry("/home/me/src/prj".into(), "Fix")).unwrap();
+        assert_eq!(json["path"], "/home/me/src/prj");
+        assert_eq!(json["type"], "Rust");
+        assert_eq!(json["vcs"], "git");
+        assert_eq!(json["worktree"]["branch"], serde_json::Value::Null);
+        assert_eq!(json["vcs_info"]["ahead_behind"], serde_json::json!([1, 0]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn paths_that_arent_utf8_are_written_as_bytes() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = PathBuf::from(OsStr::from_bytes(b"/src/caf\xe9"));
+        let json = serde_json::to_value(entry(path.clone(), "")).unwrap();
+        assert_eq!(json["path"], serde_json::json!(b"/src/caf\xe9".to_vec()));
+
+        let read = round_trip(&entry(path.clone(), ""));
+        assert_eq!(read.project.path, path);
+        assert_eq!(read.project.superproject, Some(path.join("..")));
+
+        let snapshot = Snapshot {
+            projects: vec![],
+            skipped: vec![Skipped {
+                path: path.clone(),
+                reason: "couldn't read the directory".into(),
+            }],
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let read: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.skipped[0].path, path);
+    }
+
+    #[test]
+    fn reads_requests() {
+        let request = |json| serde_json::from_str::<Request>(json);
+        assert_eq!(
+            request(r#"{"command":"projects"}"#).unwrap(),
+            Request::Projects { full_rescan: false }
+        );
+        assert_eq!(
+            request(r#"{"command":"projects","full_rescan":true}"#).unwrap(),
+            Request::Projects { full_rescan: true }
+        );
+        assert!(request(r#"{"command":"shutdown"}"#).is_err());
+        assert!(request(r#"{"full_rescan":true}"#).is_err());
+        assert!(request("{").is_err());
+    }
+}
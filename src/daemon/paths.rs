@@ -0,0 +1,75 @@
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_derive::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// JSON strings are UTF-8, so a path that isn't is written as an array of its bytes.
+/// For fields marked `#[serde(with = "crate::daemon::paths")]`.
+pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    match path.to_str() {
+        Some(path) => serializer.serialize_str(path),
+        #[cfg(unix)]
+        None => {
+            use std::os::unix::ffi::OsStrExt;
+            serializer.collect_seq(path.as_os_str().as_bytes())
+        }
+        #[cfg(not(unix))]
+        None => serializer.serialize_str(&path.to_string_lossy()),
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+    Written::deserialize(deserializer).map(PathBuf::from)
+}
+
+/// The same for an optional path, which is `null` when there isn't one
+pub mod option {
+    use super::Written;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::{Path, PathBuf};
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<PathBuf>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.as_deref().map(ToWrite).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PathBuf>, D::Error> {
+        Option::<Written>::deserialize(deserializer).map(|path| path.map(PathBuf::from))
+    }
+
+    /// A path about to be written
+    struct ToWrite<'a>(&'a Path);
+
+    impl Serialize for ToWrite<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(self.0, serializer)
+        }
+    }
+}
+
+/// A path as it's read, before it's turned back into one
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Written {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl From<Written> for PathBuf {
+    fn from(path: Written) -> PathBuf {
+        match path {
+            Written::Text(path) => PathBuf::from(path),
+            #[cfg(unix)]
+            Written::Bytes(bytes) => {
+                use std::ffi::OsString;
+                use std::os::unix::ffi::OsStringExt;
+                PathBuf::from(OsString::from_vec(bytes))
+            }
+            #[cfg(not(unix))]
+            Written::Bytes(bytes) => PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+}
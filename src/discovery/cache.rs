@@ -1,17 +1,69 @@
+use super::vcs::git::Worktree;
+use super::vcs::VcsKind;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct VcsInfo {
     pub last_commit_summary: String,
+
+    /// Unix timestamp of the last commit
+    pub last_commit_time: i64,
     pub current_branch_name: String,
     pub uncommitted_changes: usize,
+
+    /// Commits ahead of and behind the upstream branch, if there is one
+    pub ahead_behind: Option<(usize, usize)>,
+
+    /// URL of `origin`, or the first remote if there's no `origin`
+    pub remote_url: Option<String>,
+
+    /// Names of the tags pointing at the current commit
+    pub head_tags: Vec<String>,
+
+    /// For a submodule, whether the commit checked out differs from the one its
+    /// superproject records
+    pub out_of_sync: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Project {
+    #[serde(with = "crate::daemon::paths")]
     pub path: PathBuf,
+
+    /// The root this project was discovered under. Empty for aliased directories
+    /// outside every root.
+    #[serde(with = "crate::daemon::paths")]
+    pub root: PathBuf,
+
+    /// Tags from the project's own `.prj.toml`
+    pub tags: Vec<String>,
+
+    /// The kind of repository, if it is one
+    pub vcs: Option<VcsKind>,
+
+    /// From the first of the config's marker files in the project, like `Rust`
+    #[serde(rename = "type")]
+    pub project_type: Option<String>,
+
+    /// Set if the project is a linked git worktree
+    pub worktree: Option<Worktree>,
+
+    /// The repository this project is a submodule of, if it was found as one
+    #[serde(with = "crate::daemon::paths::option")]
+    pub superproject: Option<PathBuf>,
+}
+
+impl Project {
+    /// Path to the project from its root, or the root's own name if the root is a project
+    pub fn relative_path(&self) -> &Path {
+        match self.path.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => self.path.file_name().map_or(&self.path, Path::new),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -24,6 +76,8 @@ pub struct Cache {
     pub remotes: HashMap<PathBuf, Remote>,
     pub projects: HashMap<PathBuf, Project>,
     pub vcs_info: HashMap<PathBuf, VcsInfo>,
+    /// Size on disk of each project in bytes, only measured when it's shown
+    pub sizes: HashMap<PathBuf, u64>,
     update_count: i32,
 }
 
@@ -33,6 +87,7 @@ impl Cache {
             remotes: HashMap::new(),
             projects: HashMap::new(),
             vcs_info: HashMap::new(),
+            sizes: HashMap::new(),
             update_count: 0,
         }
     }
@@ -74,12 +129,39 @@ impl CacheClient {
         (*cache).vcs_info.insert(path.to_path_buf(), val);
     }
 
-    pub fn add_project(&mut self, path: PathBuf) {
+    pub fn get_size(&self, path: &Path) -> Option<u64> {
+        let cache = self.cache.lock().unwrap();
+
+        cache.sizes.get(path).copied()
+    }
+
+    pub fn add_size(&mut self, path: &Path, size: u64) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.update_count += 1;
+        cache.sizes.insert(path.to_path_buf(), size);
+    }
+
+    pub fn add_project(&self, project: Project) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.update_count += 1;
+        (*cache).projects.insert(project.path.clone(), project);
+    }
+
+    /// Replace every project with the ones from a new search, forgetting what was known
+    /// about the ones that have gone
+    pub fn set_projects(&self, projects: Vec<Project>) {
         let mut cache = self.cache.lock().unwrap();
         cache.update_count += 1;
-        (*cache)
-            .projects
-            .insert(path.to_path_buf(), Project { path });
+        cache.projects = projects.into_iter().map(|p| (p.path.clone(), p)).collect();
+
+        let Cache {
+            projects,
+            vcs_info,
+            sizes,
+            ..
+        } = &mut *cache;
+        vcs_info.retain(|path, _| projects.contains_key(path));
+        sizes.retain(|path, _| projects.contains_key(path));
     }
 
     pub fn has_new_data(&mut self) -> bool {
@@ -0,0 +1,26 @@
+use serde_derive::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Name of the file a project can use to describe itself to prj
+pub const MARKER_FILE: &str = ".prj.toml";
+
+/// Settings a project keeps for itself in its `.prj.toml`, eg.
+///
+/// ```toml
+/// tags = ["work", "team-payments"]
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Marker {
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Read the marker in a project's directory. A missing or invalid marker is treated
+/// as empty, since most projects won't have one.
+pub fn read_marker(project: &Path) -> Marker {
+    fs::read(project.join(MARKER_FILE))
+        .ok()
+        .and_then(|contents| toml::from_slice(&contents).ok())
+        .unwrap_or_default()
+}
@@ -1,3 +1,63 @@
 pub mod cache;
-pub mod git;
+pub mod marker;
+pub mod parallel;
+pub mod project_file;
+pub mod scan_cache;
+pub mod size;
 pub mod traverse;
+pub mod vcs;
+
+use crate::config::Config;
+use cache::Project;
+use parallel::walk_parallel;
+use scan_cache::ScanCache;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use traverse::{first_sighting, Seen, TraverseError};
+
+/// Search every root, calling `found` with each project as soon as it turns up, then
+/// with each aliased or pinned directory that wasn't found. Only the directories that
+/// have changed since the last search are read, unless `full_rescan`. Returns the
+/// directories that couldn't be searched.
+pub fn scan_roots<F>(config: &Config, full_rescan: bool, found: F) -> Vec<(PathBuf, TraverseError)>
+where
+    F: Fn(Project) + Sync,
+{
+    let mut scan_cache = ScanCache::autoload();
+    if full_rescan {
+        scan_cache.clear();
+    }
+
+    let seen = Seen::default();
+    let skipped = walk_parallel(
+        &config.all_roots(),
+        &config.traverse_options(),
+        config.threads(),
+        &mut scan_cache,
+        &seen,
+        |root, project| found(project.into_project(root)),
+    );
+    // Failing to save the cache only means the next search reads everything again
+    let _ = scan_cache.write();
+
+    // The same directory can be kept under a different path than it was found at
+    for project in config.extra_projects() {
+        if first_sighting(&seen, &project.path) {
+            found(project);
+        }
+    }
+    skipped
+}
+
+/// Search every root like `scan_roots`, but hand back all the projects at the end,
+/// along with the directories that couldn't be searched
+pub fn find_projects(
+    config: &Config,
+    full_rescan: bool,
+) -> (Vec<Project>, Vec<(PathBuf, TraverseError)>) {
+    let found = Mutex::new(vec![]);
+    let skipped = scan_roots(config, full_rescan, |project| {
+        found.lock().unwrap().push(project)
+    });
+    (found.into_inner().unwrap(), skipped)
+}
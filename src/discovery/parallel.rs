@@ -0,0 +1,233 @@
+use super::scan_cache::ScanCache;
+use super::traverse::{Depth, FoundProject, Seen, TraverseError, TraverseOptions, Walk};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// A directory waiting to be read: which root it's under, its path and how deep it is
+type Job = (usize, PathBuf, Depth);
+
+/// Where threads with nothing to do wait. They look for work once more while holding
+/// the lock, and anyone who queues more or finishes the last of it takes the lock
+/// before waking them, so they can't miss it.
+#[derive(Default)]
+struct Idle {
+    /// How many threads are waiting
+    sleeping: Mutex<usize>,
+    wake: Condvar,
+}
+
+impl Idle {
+    /// Wake the waiting threads, if there are any
+    fn wake_all(&self) {
+        if *self.sleeping.lock().unwrap() > 0 {
+            self.wake.notify_all();
+        }
+    }
+}
+
+/// How many threads to use when the config leaves it up to us
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Search every root at once on `threads` threads, calling `found` with each project
+/// and the root it's under as soon as it turns up. Each thread works through its own
+/// queue of directories, and steals from the others when it runs out, so one deep
+/// tree doesn't leave the rest of the threads idle. Directories that haven't changed
+/// since `cache` was filled aren't read again, and afterwards it holds what this search
+/// found. Projects already in `seen` are skipped, and each one found is added. Returns
+/// the directories that couldn't be searched.
+pub fn walk_parallel<F>(
+    roots: &[&Path],
+    options: &TraverseOptions,
+    threads: usize,
+    cache: &mut ScanCache,
+    seen: &Seen,
+    found: F,
+) -> Vec<(PathBuf, TraverseError)>
+where
+    F: Fn(&Path, FoundProject) + Sync,
+{
+    let threads = threads.max(1);
+    let previous = Arc::new(cache.take_listings(options));
+    // A project under more than one root is only returned once
+    let walks: Vec<Walk> = roots
+        .iter()
+        .map(|root| Walk::new(root, options, previous.clone(), seen.clone()))
+        .collect();
+    let queues: Vec<Mutex<VecDeque<Job>>> =
+        (0..threads).map(|_| Mutex::new(VecDeque::new())).collect();
+
+    // Directories queued or being read. Once it drops to zero there's nothing left
+    // that could queue any more.
+    let outstanding = AtomicUsize::new(roots.len());
+    for (index, root) in roots.iter().enumerate() {
+        let queue = &queues[index % threads];
+        queue
+            .lock()
+            .unwrap()
+            .push_back((index, root.to_path_buf(), Depth::default()));
+    }
+
+    let idle = Idle::default();
+
+    thread::scope(|scope| {
+        for worker in 0..threads {
+            let (walks, queues, outstanding, idle, found) =
+                (&walks, &queues, &outstanding, &idle, &found);
+            scope.spawn(move || loop {
+                let job = next_job(queues, worker)
+                    .or_else(|| wait_for_job(queues, worker, outstanding, idle));
+                let (root, path, depth) = match job {
+                    Some(job) => job,
+                    None => return,
+                };
+
+                let walk = &walks[root];
+                let (project, subdirs) = walk.read_directory(path, depth);
+
+                // Before anyone can read the directories under it, so its submodules
+                // are claimed as submodules rather than turning up as projects of
+                // their own
+                if let Some((path, vcs, project_type)) = project {
+                    for project in walk.found(path, vcs, project_type) {
+                        found(walk.root(), project);
+                    }
+                }
+
+                // Count the new directories before this one is finished, so the count
+                // can't touch zero while there's still work
+                let queued = !subdirs.is_empty();
+                outstanding.fetch_add(subdirs.len(), Ordering::SeqCst);
+                queues[worker]
+                    .lock()
+                    .unwrap()
+                    .extend(subdirs.into_iter().map(|(path, depth)| (root, path, depth)));
+                if queued {
+                    idle.wake_all();
+                }
+
+                // The others are waiting to hear there's nothing left
+                if outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    idle.wake_all();
+                }
+            });
+        }
+    });
+
+    cache.set_listings(walks.iter().flat_map(Walk::take_listings).collect());
+    walks.iter().flat_map(Walk::take_skipped).collect()
+}
+
+/// The newest directory on this thread's own queue, which keeps its work close
+/// together, or failing that the oldest on someone else's, which is likely to be
+/// near the top of a tree with plenty under it
+fn next_job(queues: &[Mutex<VecDeque<Job>>], worker: usize) -> Option<Job> {
+    if let Some(job) = queues[worker].lock().unwrap().pop_back() {
+        return Some(job);
+    }
+
+    (1..queues.len())
+        .map(|offset| (worker + offset) % queues.len())
+        .find_map(|victim| queues[victim].lock().unwrap().pop_front())
+}
+
+/// Wait until there's a directory to read, or `None` once every directory has been read
+fn wait_for_job(
+    queues: &[Mutex<VecDeque<Job>>],
+    worker: usize,
+    outstanding: &AtomicUsize,
+    idle: &Idle,
+) -> Option<Job> {
+    let mut sleeping = idle.sleeping.lock().unwrap();
+    loop {
+        if let Some(job) = next_job(queues, worker) {
+            return Some(job);
+        }
+        if outstanding.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+        *sleeping += 1;
+        sleeping = idle.wake.wait(sleeping).unwrap();
+        *sleeping -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::traverse::Traverser;
+    use crate::test_dir::TestDir;
+    use git2::Repository;
+
+    /// Every project found, and the repository it's a submodule of
+    type Found = Vec<(PathBuf, Option<PathBuf>)>;
+
+    fn traverse(roots: &[&Path], options: &TraverseOptions) -> Found {
+        let seen = Seen::default();
+        let mut found: Found = roots
+            .iter()
+            .flat_map(|root| Traverser::new(root, options, seen.clone()))
+            .map(|p| (p.path, p.superproject))
+            .collect();
+        found.sort();
+        found
+    }
+
+    fn walk(roots: &[&Path], options: &TraverseOptions, threads: usize) -> Found {
+        let found = Mutex::new(vec![]);
+        let skipped = walk_parallel(
+            roots,
+            options,
+            threads,
+            &mut ScanCache::default(),
+            &Seen::default(),
+            |_, p| found.lock().unwrap().push((p.path, p.superproject)),
+        );
+        assert!(skipped.is_empty());
+        let mut found = found.into_inner().unwrap();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn finds_what_the_traverser_finds() {
+        let root = TestDir::new("parallel");
+        for group in 0..4 {
+            for project in 0..5 {
+                root.dir(format!("g{}/p{}/.hg", group, project));
+                root.dir(format!("g{}/p{}/src/inner/.hg", group, project));
+            }
+        }
+        let superproject = root.join("g0/super");
+        Repository::init(&superproject).unwrap();
+        Repository::init(superproject.join("sub")).unwrap();
+        root.file(
+            "g0/super/.gitmodules",
+            "[submodule \"sub\"]\n\tpath = sub\n\turl = ../sub\n",
+        );
+
+        let options = TraverseOptions {
+            nested: true,
+            submodules: true,
+            ..TraverseOptions::default()
+        };
+        let expected = traverse(&[root.path()], &options);
+        assert_eq!(expected.len(), 42);
+        assert!(expected.contains(&(superproject.join("sub"), Some(superproject.clone()))));
+
+        let group = root.join("g1");
+        let project = root.join("g1/p1");
+        let overlapping: &[&Path] = &[root.path(), &group, &project];
+        for _ in 0..20 {
+            for &threads in &[1, 4] {
+                assert_eq!(walk(&[root.path()], &options, threads), expected);
+                assert_eq!(walk(overlapping, &options, threads), expected);
+            }
+        }
+        assert_eq!(traverse(overlapping, &options), expected);
+    }
+}
@@ -0,0 +1,107 @@
+use regex::Regex;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
+/// Types of project for well known files. Anything else is shown as it's written in
+/// the config.
+const KNOWN_TYPES: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("package.json", "Node"),
+    ("deno.json", "Deno"),
+    ("go.mod", "Go"),
+    ("pyproject.toml", "Python"),
+    ("setup.py", "Python"),
+    ("requirements.txt", "Python"),
+    ("Gemfile", "Ruby"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("build.gradle.kts", "Kotlin"),
+    ("composer.json", "PHP"),
+    ("mix.exs", "Elixir"),
+    ("Package.swift", "Swift"),
+    ("pubspec.yaml", "Dart"),
+    ("dune-project", "OCaml"),
+    ("*.cabal", "Haskell"),
+    ("*.csproj", ".NET"),
+    ("*.sln", ".NET"),
+    ("CMakeLists.txt", "C/C++"),
+    ("Makefile", "Make"),
+];
+
+/// A file from the config's `markers` that makes a directory a project even if it
+/// isn't a repository. Either a name like `Cargo.toml` or a glob like `*.csproj`.
+#[derive(Debug, Clone)]
+pub struct ProjectFile {
+    pattern: String,
+    /// Set if the pattern has wildcards in it
+    glob: Option<Regex>,
+}
+
+impl ProjectFile {
+    pub fn new(pattern: &str) -> ProjectFile {
+        let glob = match pattern.contains(['*', '?']) {
+            true => {
+                let regex: String = pattern
+                    .chars()
+                    .map(|c| match c {
+                        '*' => String::from(".*"),
+                        '?' => String::from("."),
+                        c => regex::escape(&c.to_string()),
+                    })
+                    .collect();
+                Regex::new(&format!("^{}$", regex)).ok()
+            }
+            false => None,
+        };
+
+        ProjectFile {
+            pattern: pattern.to_string(),
+            glob,
+        }
+    }
+
+    /// The name or glob as it's written in the config
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn matches(&self, file_name: &OsStr) -> bool {
+        match &self.glob {
+            Some(glob) => file_name.to_str().is_some_and(|name| glob.is_match(name)),
+            None => self.pattern.as_str() == file_name,
+        }
+    }
+
+    /// What the file says about the project, like `Rust` for `Cargo.toml`
+    pub fn project_type(&self) -> String {
+        KNOWN_TYPES
+            .iter()
+            .find(|(pattern, _)| *pattern == self.pattern)
+            .map_or_else(|| self.pattern.clone(), |(_, name)| name.to_string())
+    }
+}
+
+/// The type of the first of `files` that matches any of the names in a directory.
+/// Earlier files in the config win when there's more than one.
+pub fn project_type<'a>(
+    files: &[ProjectFile],
+    names: impl Iterator<Item = &'a OsStr> + Clone,
+) -> Option<String> {
+    files
+        .iter()
+        .find(|file| names.clone().any(|name| file.matches(name)))
+        .map(ProjectFile::project_type)
+}
+
+/// The type of project in a directory that's found some other way than traversing
+pub fn detect_type(path: &Path, files: &[ProjectFile]) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+
+    let names: Vec<OsString> = path
+        .read_dir()
+        .map(|listing| listing.flatten().map(|entry| entry.file_name()).collect())
+        .unwrap_or_default();
+    project_type(files, names.iter().map(OsString::as_os_str))
+}
@@ -0,0 +1,187 @@
+use super::traverse::TraverseOptions;
+use super::vcs::VcsKind;
+use crate::config::{ReadError, WriteError};
+use dirs::home_dir;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// What was found in a directory the last time it was read. Adding or removing an
+/// entry changes a directory's modification time, so while that stays the same the
+/// directory doesn't need to be read again.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Listing {
+    /// Modification time of the directory when it was read, in nanoseconds since the
+    /// Unix epoch
+    pub modified: u64,
+
+    /// The kind of repository, if the directory is one
+    pub vcs: Option<VcsKind>,
+
+    /// From the first of the config's marker files in the directory
+    pub project_type: Option<String>,
+
+    /// Set if the directory is a bare git repository, which isn't looked inside
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub bare: bool,
+
+    /// Names of the directories inside
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subdirs: Vec<PathBuf>,
+}
+
+/// Every directory read by the last search, so the next one only has to read the
+/// directories that have changed since
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ScanCache {
+    /// Location of the cache file
+    #[serde(skip)]
+    pub location: PathBuf,
+
+    /// The `markers` the directories were read with. The types found depend on them.
+    #[serde(default)]
+    markers: Vec<String>,
+
+    /// Whether symlinked directories were counted as directories
+    #[serde(default)]
+    follow_symlinks: bool,
+
+    #[serde(default)]
+    directories: HashMap<PathBuf, Listing>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// A directory's modification time in nanoseconds since the Unix epoch
+pub fn modified(metadata: &Metadata) -> Option<u64> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_nanos() as u64)
+}
+
+impl ScanCache {
+    /// Load the cache from the home directory. A missing or unreadable cache is
+    /// treated as empty, so everything is read and the cache is recreated.
+    pub fn autoload() -> ScanCache {
+        let location = match home_dir() {
+            Some(home) => home.join(".prj_cache"),
+            None => return ScanCache::default(),
+        };
+
+        ScanCache::load_from(&location).unwrap_or(ScanCache {
+            location,
+            ..ScanCache::default()
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<ScanCache, ReadError> {
+        if !path.exists() {
+            return Err(ReadError::Missing);
+        }
+
+        let mut f = File::open(path).or(Err(ReadError::IoError))?;
+        let mut buffer = Vec::new();
+        f.read_to_end(&mut buffer).or(Err(ReadError::IoError))?;
+
+        let mut cache: ScanCache = toml::from_slice(&buffer).or(Err(ReadError::ParseError))?;
+        cache.location = path.into();
+
+        Ok(cache)
+    }
+
+    pub fn write(&self) -> Result<(), WriteError> {
+        if self.location.as_os_str().is_empty() {
+            return Ok(());
+        }
+
+        let output = toml::to_vec(self).or(Err(WriteError::SerializeError))?;
+        let mut f = File::create(&self.location).map_err(WriteError::IoError)?;
+
+        f.write_all(&output).map_err(WriteError::IoError)
+    }
+
+    /// Forget every directory, so the next search reads them all again
+    pub fn clear(&mut self) {
+        self.directories.clear();
+    }
+
+    /// Hand over the directories read last time, for a search with `options`. If the
+    /// options have changed since, what was found then can't be trusted, so there are none.
+    pub fn take_listings(&mut self, options: &TraverseOptions) -> HashMap<PathBuf, Listing> {
+        let markers: Vec<String> = options
+            .markers
+            .iter()
+            .map(|file| file.pattern().to_string())
+            .collect();
+        let directories = std::mem::take(&mut self.directories);
+
+        if markers != self.markers || options.follow_symlinks != self.follow_symlinks {
+            self.markers = markers;
+            self.follow_symlinks = options.follow_symlinks;
+            return HashMap::new();
+        }
+        directories
+    }
+
+    /// Keep the directories read by a search, replacing the ones from before
+    pub fn set_listings(&mut self, listings: HashMap<PathBuf, Listing>) {
+        self.directories = listings;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::project_file::ProjectFile;
+
+    fn cache_with(options: &TraverseOptions) -> ScanCache {
+        let mut cache = ScanCache::default();
+        cache.take_listings(options);
+        let listing = Listing {
+            subdirs: vec![PathBuf::from("project")],
+            ..Listing::default()
+        };
+        cache.set_listings(vec![(PathBuf::from("/src"), listing)].into_iter().collect());
+        cache
+    }
+
+    #[test]
+    fn listings_are_kept_while_the_options_stay_the_same() {
+        let options = TraverseOptions {
+            markers: vec![ProjectFile::new("Cargo.toml")],
+            ..TraverseOptions::default()
+        };
+        let mut cache = cache_with(&options);
+        assert!(cache
+            .take_listings(&options)
+            .contains_key(Path::new("/src")));
+        // They're handed over, not copied
+        assert!(cache.take_listings(&options).is_empty());
+    }
+
+    #[test]
+    fn changing_the_options_discards_the_listings() {
+        let options = TraverseOptions::default();
+
+        let markers = TraverseOptions {
+            markers: vec![ProjectFile::new("Cargo.toml")],
+            ..options.clone()
+        };
+        assert!(cache_with(&options).take_listings(&markers).is_empty());
+
+        let follow_symlinks = TraverseOptions {
+            follow_symlinks: true,
+            ..options.clone()
+        };
+        let mut cache = cache_with(&options);
+        assert!(cache.take_listings(&follow_symlinks).is_empty());
+
+        // Until they've been read again with the new options
+        cache.set_listings(cache_with(&follow_symlinks).directories);
+        assert_eq!(cache.take_listings(&follow_symlinks).len(), 1);
+    }
+}
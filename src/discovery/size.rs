@@ -0,0 +1,20 @@
+use std::fs;
+use std::path::Path;
+
+/// Total size of the files under a directory in bytes. Symlinks aren't followed, and
+/// anything that can't be read is skipped.
+pub fn directory_size(path: &Path) -> u64 {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => directory_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
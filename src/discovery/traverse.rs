@@ -1,76 +1,678 @@
+use super::cache::Project;
+use super::marker::{read_marker, Marker};
+use super::project_file::{detect_type, project_type, ProjectFile};
+use super::scan_cache::{modified, Listing};
+use super::vcs::git::{is_bare_repo, linked_worktrees, read_worktree, submodules, Worktree};
+use super::vcs::VcsKind;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
+/// Directories changed this close to the start of a search might change again without
+/// their modification time moving on, on filesystems that only keep whole seconds
+const MODIFIED_GRACE: Duration = Duration::from_secs(2);
+
+/// A project found while traversing
+#[derive(Debug)]
+pub struct FoundProject {
+    pub path: PathBuf,
+    /// The kind of repository, or `None` if only a marker file was found
+    pub vcs: Option<VcsKind>,
+    /// From the first of the config's marker files in the project, like `Rust`
+    pub project_type: Option<String>,
+    /// The project's `.prj.toml`, read as soon as the project is found
+    pub marker: Marker,
+    /// Set if the project is a linked git worktree
+    pub worktree: Option<Worktree>,
+    /// The repository this project is a submodule of, if it was found as one
+    pub superproject: Option<PathBuf>,
+}
+
+impl FoundProject {
+    pub fn into_project(self, root: &Path) -> Project {
+        Project {
+            path: self.path,
+            root: root.to_path_buf(),
+            tags: self.marker.tags,
+            vcs: self.vcs,
+            project_type: self.project_type,
+            worktree: self.worktree,
+            superproject: self.superproject,
+        }
+    }
+}
+
+/// What to look for while traversing, taken from the config
+#[derive(Debug, Clone)]
+pub struct TraverseOptions {
+    /// How many directories deep to look under a root, and with `nested`, under the
+    /// outermost project
+    pub max_nesting: u8,
+    /// Whether to list bare repositories as projects
+    pub bare_repos: bool,
+    /// Keep looking for projects inside projects
+    pub nested: bool,
+    /// List the submodules of git repositories as projects of their own
+    pub submodules: bool,
+    /// Files that make a directory a project even if it isn't a repository
+    pub markers: Vec<ProjectFile>,
+    /// Look inside symlinked directories. The root itself is always followed.
+    pub follow_symlinks: bool,
+}
+
+impl Default for TraverseOptions {
+    fn default() -> TraverseOptions {
+        TraverseOptions {
+            max_nesting: 3,
+            bare_repos: false,
+            nested: false,
+            submodules: false,
+            markers: vec![],
+            follow_symlinks: false,
+        }
+    }
+}
+
+/// A project's directory, the kind of repository and the type from its marker files
+pub type FoundPath = (PathBuf, Option<VcsKind>, Option<String>);
+
+/// How deep a directory is. Counted from the root until a project is found, then, with
+/// `nested`, from that project. Projects inside it don't start the count again, or a
+/// marker like `package.json` in every package under `node_modules` would leave no limit.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Depth {
+    pub level: u8,
+    /// Set below a project
+    pub in_project: bool,
+}
+
+/// Identifies a directory however it was reached
+#[cfg(unix)]
+pub type DirectoryId = (u64, u64);
+#[cfg(not(unix))]
+pub type DirectoryId = PathBuf;
+
+/// Every project returned so far by the searches sharing it, since a project can be
+/// reached through symlinks, under more than one root, and worktrees and submodules
+/// through their repository as well as by traversing
+pub type Seen = Arc<Mutex<HashSet<DirectoryId>>>;
+
+/// Whether a project isn't in `seen` yet, by any path. Adds it if not.
+pub fn first_sighting(seen: &Seen, path: &Path) -> bool {
+    fs::metadata(path)
+        .ok()
+        .and_then(|metadata| directory_id(path, &metadata))
+        .is_none_or(|id| seen.lock().unwrap().insert(id))
+}
+
+#[cfg(unix)]
+fn directory_id(_path: &Path, metadata: &fs::Metadata) -> Option<DirectoryId> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn directory_id(path: &Path, _metadata: &fs::Metadata) -> Option<DirectoryId> {
+    path.canonicalize().ok()
+}
+
+/// The state of a search under one root, shared by every thread searching it
+pub struct Walk {
+    options: TraverseOptions,
+    root: PathBuf,
+    /// The root with any symlinks resolved
+    real_root: Option<PathBuf>,
+    /// Every directory read so far, so symlinks can't lead round in circles
+    visited: Mutex<HashSet<DirectoryId>>,
+    seen: Seen,
+    /// Directories that couldn't be searched, or only partly
+    skipped: Mutex<Vec<(PathBuf, TraverseError)>>,
+    /// What the last search found in each directory, to skip reading the unchanged ones
+    previous: Arc<HashMap<PathBuf, Listing>>,
+    /// What this search found in each directory, for the next one
+    listings: Mutex<HashMap<PathBuf, Listing>>,
+    /// Directories modified after this are read again next time even if they don't change
+    trust_before: Option<u64>,
+}
+
+/// Searches a root one directory at a time on the current thread. The commands all use
+/// [`walk_parallel`](super::parallel::walk_parallel), so this is what it's checked against.
+#[cfg(test)]
 pub struct Traverser {
-    queue: Vec<(PathBuf, u8)>,
-    max_nesting: u8,
+    walk: Walk,
+    queue: Vec<(PathBuf, Depth)>,
+    /// Worktrees and submodules of a repository that was just found, waiting to be returned
+    pending: Vec<FoundProject>,
 }
 
+/// Why a directory couldn't be searched
 #[derive(Debug)]
 pub enum TraverseError {
-    FailedToReadDir,
-    InvalidFilename,
-    Finished,
+    FailedToReadDir(io::Error),
+    /// One entry in the directory couldn't be read, the others still were
+    FailedToReadEntry(io::Error),
 }
 
+impl std::fmt::Display for TraverseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TraverseError::FailedToReadDir(e) => write!(f, "couldn't read the directory: {}", e),
+            TraverseError::FailedToReadEntry(e) => write!(f, "couldn't read an entry: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
 impl Iterator for Traverser {
-    type Item = PathBuf;
+    type Item = FoundProject;
 
-    fn next(&mut self) -> Option<PathBuf> {
-        loop {
-            match self.find_project() {
-                // We found a project
-                Ok(path) => return Some(path),
+    fn next(&mut self) -> Option<FoundProject> {
+        if let Some(found) = self.pending.pop() {
+            return Some(found);
+        }
 
-                // Nothing left to search
-                Err(TraverseError::Finished) => return None,
+        // Keep searching until there's a project that hasn't been seen, or nothing left
+        while let Some((path, depth)) = self.queue.pop() {
+            let (found, subdirs) = self.walk.read_directory(path, depth);
+            self.queue.extend(subdirs);
 
-                // Ignore other errors, keep searching
-                Err(_) => (),
-            };
+            if let Some((path, vcs, project_type)) = found {
+                let mut found = self.walk.found(path, vcs, project_type).into_iter();
+                if let Some(project) = found.next() {
+                    self.pending.extend(found.rev());
+                    return Some(project);
+                }
+            }
         }
+        None
     }
 }
 
+#[cfg(test)]
 impl Traverser {
-    pub fn new(path: &Path, max_nesting: u8) -> Self {
+    /// Search under `path`, skipping the projects in `seen`, which other searches can
+    /// share to search more roots
+    pub fn new(path: &Path, options: &TraverseOptions, seen: Seen) -> Self {
         let mut queue = Vec::with_capacity(60);
-        queue.push((path.to_path_buf(), 0));
+        queue.push((path.to_path_buf(), Depth::default()));
 
-        Traverser { queue, max_nesting }
+        Traverser {
+            walk: Walk::new(path, options, Arc::default(), seen),
+            queue,
+            pending: vec![],
+        }
     }
+}
 
-    fn find_project(&mut self) -> Result<PathBuf, TraverseError> {
-        while let Some((path, nesting_level)) = self.queue.pop() {
-            let listing = path.read_dir().or(Err(TraverseError::FailedToReadDir))?;
+impl Walk {
+    /// Start a search under `root`. Directories that haven't changed since they were
+    /// read into `previous` are taken from there instead of being read again, and
+    /// projects in `seen` aren't returned again.
+    pub fn new(
+        root: &Path,
+        options: &TraverseOptions,
+        previous: Arc<HashMap<PathBuf, Listing>>,
+        seen: Seen,
+    ) -> Walk {
+        let trust_before = SystemTime::now()
+            .checked_sub(MODIFIED_GRACE)
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_nanos() as u64);
 
-            let mut subdirs = vec![];
+        Walk {
+            options: options.clone(),
+            root: root.to_path_buf(),
+            real_root: root.canonicalize().ok(),
+            visited: Mutex::new(HashSet::new()),
+            seen,
+            skipped: Mutex::new(vec![]),
+            previous,
+            listings: Mutex::new(HashMap::new()),
+            trust_before,
+        }
+    }
 
-            for entry_result in listing {
-                let entry = entry_result.or(Err(TraverseError::FailedToReadDir))?;
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
 
-                let file_name = entry
-                    .file_name()
-                    .into_string()
-                    .or(Err(TraverseError::InvalidFilename))?;
+    pub fn take_skipped(&self) -> Vec<(PathBuf, TraverseError)> {
+        std::mem::take(&mut *self.skipped.lock().unwrap())
+    }
 
-                if file_name == ".git" {
-                    // We're in a project! Add it and skip any more traversal
-                    return Ok(path.to_path_buf());
-                }
+    pub fn take_listings(&self) -> HashMap<PathBuf, Listing> {
+        std::mem::take(&mut *self.listings.lock().unwrap())
+    }
+
+    fn skip(&self, path: PathBuf, reason: TraverseError) {
+        self.skipped.lock().unwrap().push((path, reason));
+    }
 
-                if entry.path().is_dir() {
-                    subdirs.push(entry.path());
+    /// The path a project should be shown with. Projects reached through a symlink are
+    /// shown where they really are, as long as that's under the root too, so they look
+    /// the same whichever way they were found first.
+    fn preferred_path(&self, path: PathBuf) -> PathBuf {
+        let real_root = match &self.real_root {
+            Some(real_root) => real_root,
+            None => return path,
+        };
+        match path.canonicalize() {
+            Ok(real) => match real.strip_prefix(real_root) {
+                Ok(relative) => self.root.join(relative),
+                Err(_) => path,
+            },
+            Err(_) => path,
+        }
+    }
+
+    /// Read what's needed about a project that was just found, followed by any worktrees
+    /// linked to it and, if asked for, its submodules. Empty if it's been found before.
+    pub fn found(
+        &self,
+        path: PathBuf,
+        vcs: Option<VcsKind>,
+        project_type: Option<String>,
+    ) -> Vec<FoundProject> {
+        if !first_sighting(&self.seen, &path) {
+            return vec![];
+        }
+        let path = match self.options.follow_symlinks {
+            true => self.preferred_path(path),
+            false => path,
+        };
+
+        let mut projects = vec![FoundProject {
+            marker: read_marker(&path),
+            worktree: read_worktree(&path),
+            superproject: None,
+            path: path.clone(),
+            vcs,
+            project_type,
+        }];
+
+        if vcs == Some(VcsKind::Git) {
+            let git_dir = match is_bare_repo(&path) {
+                true => path.clone(),
+                false => path.join(".git"),
+            };
+            for worktree in linked_worktrees(&git_dir) {
+                let project_type = detect_type(&worktree, &self.options.markers);
+                projects.extend(self.found(worktree, Some(VcsKind::Git), project_type));
+            }
+
+            let submodules = match self.options.submodules {
+                true => submodules(&path),
+                false => vec![],
+            };
+            for submodule in submodules {
+                let project_type = detect_type(&submodule, &self.options.markers);
+                let mut found = self.found(submodule, Some(VcsKind::Git), project_type);
+                if let Some(first) = found.first_mut() {
+                    first.superproject = Some(path.clone());
                 }
+                projects.extend(found);
+            }
+        }
+
+        projects
+    }
+
+    /// Look through one directory. Returns the project it holds, if it is one, and the
+    /// subdirectories still to search with how deep they are.
+    pub fn read_directory(
+        &self,
+        path: PathBuf,
+        depth: Depth,
+    ) -> (Option<FoundPath>, Vec<(PathBuf, Depth)>) {
+        // One `stat` is all an unchanged directory costs
+        let metadata = fs::metadata(&path).ok();
+
+        // Symlinks can lead back to a directory that's already been read
+        if let Some(id) = metadata.as_ref().and_then(|m| directory_id(&path, m)) {
+            if !self.visited.lock().unwrap().insert(id) {
+                return (None, vec![]);
             }
+        }
+
+        let modified = metadata.as_ref().and_then(modified);
+        let (listing, complete) = match self.previous.get(&path) {
+            Some(listing) if modified == Some(listing.modified) => (listing.clone(), true),
+            _ => match self.list(&path) {
+                Some((listing, complete)) => (
+                    Listing {
+                        modified: modified.unwrap_or(0),
+                        ..listing
+                    },
+                    complete && modified.is_some(),
+                ),
+                None => return (None, vec![]),
+            },
+        };
+        if complete {
+            self.remember(&path, &listing);
+        }
+
+        // There's nothing to find inside a bare repository
+        if listing.bare {
+            return match depth.level > 0 && self.options.bare_repos {
+                true => (Some((path, Some(VcsKind::Git), None)), vec![]),
+                false => (None, vec![]),
+            };
+        }
+
+        let found = listing.vcs.is_some() || listing.project_type.is_some();
 
-            // We mustn't have found a project in this directory, queue up the subdirectories
-            for subdir in subdirs {
-                if nesting_level < self.max_nesting {
-                    self.queue.push((subdir.to_path_buf(), nesting_level + 1));
+        // Inside a project, nested projects can go as deep again as they could under a root
+        let depth = match found && !depth.in_project {
+            true => Depth {
+                level: 0,
+                in_project: true,
+            },
+            false => depth,
+        };
+        let subdirs =
+            match (!found || self.options.nested) && depth.level < self.options.max_nesting {
+                true => listing
+                    .subdirs
+                    .iter()
+                    .map(|name| {
+                        let depth = Depth {
+                            level: depth.level + 1,
+                            ..depth
+                        };
+                        (path.join(name), depth)
+                    })
+                    .collect(),
+                false => vec![],
+            };
+
+        // If we're in a project, unless nested projects are wanted, skip any more traversal
+        match found {
+            true => (Some((path, listing.vcs, listing.project_type)), subdirs),
+            false => (None, subdirs),
+        }
+    }
+
+    /// Keep what was found in a directory for the next search, unless it might change
+    /// without anyone noticing. The cache is saved as TOML, which only holds UTF-8.
+    fn remember(&self, path: &Path, listing: &Listing) {
+        let settled = self
+            .trust_before
+            .is_some_and(|trust_before| listing.modified < trust_before);
+        let utf8 = path.to_str().is_some() && listing.subdirs.iter().all(|s| s.to_str().is_some());
+
+        if settled && utf8 {
+            self.listings
+                .lock()
+                .unwrap()
+                .insert(path.to_path_buf(), listing.clone());
+        }
+    }
+
+    /// Read a directory from disk, and whether every entry could be read. `None` if
+    /// the directory couldn't be read at all.
+    fn list(&self, path: &Path) -> Option<(Listing, bool)> {
+        if is_bare_repo(path) {
+            let listing = Listing {
+                bare: true,
+                ..Listing::default()
+            };
+            return Some((listing, true));
+        }
+
+        let entries = match path.read_dir() {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.skip(path.to_path_buf(), TraverseError::FailedToReadDir(e));
+                return None;
+            }
+        };
+
+        let mut subdirs = vec![];
+        let mut names = vec![];
+        let mut vcs: Option<VcsKind> = None;
+        let mut complete = true;
+
+        for entry_result in entries {
+            // Skip just this entry, the rest of the directory might still be fine
+            let entry = match entry_result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.skip(path.to_path_buf(), TraverseError::FailedToReadEntry(e));
+                    complete = false;
+                    continue;
                 }
+            };
+
+            // Names don't have to be UTF-8, but every marker is
+            let file_name = entry.file_name();
+            let marker = file_name.to_str().and_then(VcsKind::from_marker);
+
+            // A `.git` file rather than a directory still marks a worktree or submodule
+            if let Some(kind) = marker {
+                // Keep looking in case another kind of repository shares the directory
+                vcs = Some(vcs.map_or(kind, |v| v.preferred(kind)));
+                continue;
+            }
+
+            let is_dir = match self.options.follow_symlinks {
+                true => entry.path().is_dir(),
+                false => entry.file_type().is_ok_and(|t| t.is_dir()),
+            };
+            if is_dir {
+                subdirs.push(PathBuf::from(&file_name));
+            }
+
+            if !self.options.markers.is_empty() {
+                names.push(file_name);
             }
         }
 
-        Err(TraverseError::Finished)
+        let listing = Listing {
+            modified: 0,
+            vcs,
+            project_type: project_type(
+                &self.options.markers,
+                names.iter().map(OsString::as_os_str),
+            ),
+            bare: false,
+            subdirs,
+        };
+        Some((listing, complete))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn nested_projects_dont_reset_the_depth() {
+        let root = TestDir::new("nested");
+        root.dir("outer/.hg");
+        root.file("outer/node_modules/first/package.json", "{}");
+        root.file(
+            "outer/node_modules/first/node_modules/second/package.json",
+            "{}",
+        );
+        let outer = root.join("outer");
+        let first = root.join("outer/node_modules/first");
+
+        let options = TraverseOptions {
+            nested: true,
+            markers: vec![ProjectFile::new("package.json")],
+            ..TraverseOptions::default()
+        };
+        let mut found: Vec<PathBuf> = Traverser::new(root.path(), &options, Seen::default())
+            .map(|p| p.path)
+            .collect();
+        found.sort();
+        // `second` is four directories below `outer`
+        assert_eq!(found, vec![outer.clone(), first]);
+
+        let options = TraverseOptions {
+            nested: false,
+            ..options
+        };
+        let found: Vec<PathBuf> = Traverser::new(root.path(), &options, Seen::default())
+            .map(|p| p.path)
+            .collect();
+        assert_eq!(found, vec![outer]);
+    }
+
+    #[test]
+    fn projects_under_two_roots_are_returned_once() {
+        let root = TestDir::new("roots");
+        root.dir("group/project/.hg");
+        let options = TraverseOptions::default();
+
+        let seen = Seen::default();
+        let found: Vec<PathBuf> = [root.path().to_path_buf(), root.join("group")]
+            .iter()
+            .flat_map(|root| Traverser::new(root, &options, seen.clone()))
+            .map(|p| p.path)
+            .collect();
+        assert_eq!(found, vec![root.join("group/project")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_followed_once() {
+        let root = TestDir::new("symlinks");
+        root.dir("a/project/.hg");
+        // Back up to the root, and across to `a` again
+        root.symlink("a/loop", "..");
+        root.symlink("b/link", "../a");
+        let outside = TestDir::new("symlinks-outside");
+        outside.dir("elsewhere/.hg");
+        root.symlink("c", outside.path());
+
+        let options = TraverseOptions {
+            follow_symlinks: true,
+            ..TraverseOptions::default()
+        };
+        let mut found: Vec<PathBuf> = Traverser::new(root.path(), &options, Seen::default())
+            .map(|p| p.path)
+            .collect();
+        found.sort();
+        // Shown where it really is when that's under the root, and where the symlink is
+        // when it isn't
+        assert_eq!(
+            found,
+            vec![root.join("a/project"), root.join("c/elsewhere")]
+        );
+
+        // The loop leads back to the root, which has already been read
+        let walk = Walk::new(root.path(), &options, Arc::default(), Seen::default());
+        let (_, subdirs) = walk.read_directory(root.path().to_path_buf(), Depth::default());
+        assert_eq!(subdirs.len(), 3);
+        let (found, subdirs) = walk.read_directory(root.join("a/loop"), Depth::default());
+        assert!(found.is_none());
+        assert!(subdirs.is_empty());
+    }
+
+    /// Set a directory's modification time well before the search, and return it as
+    /// the cache keeps it
+    fn settle(path: &Path) -> u64 {
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        fs::File::open(path)
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+        modified(&fs::metadata(path).unwrap()).unwrap()
+    }
+
+    fn read(walk: &Walk, path: &Path) -> Vec<PathBuf> {
+        let (_, subdirs) = walk.read_directory(path.to_path_buf(), Depth::default());
+        subdirs.into_iter().map(|(path, _)| path).collect()
+    }
+
+    #[test]
+    fn unchanged_directories_are_taken_from_the_cache() {
+        let root = TestDir::new("rescan");
+        root.dir("on-disk");
+        let modified = settle(root.path());
+        let cached = Listing {
+            modified,
+            subdirs: vec![PathBuf::from("cached")],
+            ..Listing::default()
+        };
+        let previous: HashMap<_, _> = vec![(root.path().to_path_buf(), cached)]
+            .into_iter()
+            .collect();
+        let options = TraverseOptions::default();
+
+        let walk = Walk::new(
+            root.path(),
+            &options,
+            Arc::new(previous.clone()),
+            Seen::default(),
+        );
+        assert_eq!(read(&walk, root.path()), vec![root.join("cached")]);
+        assert_eq!(
+            walk.take_listings()[root.path()].subdirs,
+            vec![PathBuf::from("cached")]
+        );
+
+        // Anything added or removed since moves the modification time on
+        let mut changed = previous;
+        changed.get_mut(root.path()).unwrap().modified -= 1;
+        let walk = Walk::new(root.path(), &options, Arc::new(changed), Seen::default());
+        assert_eq!(read(&walk, root.path()), vec![root.join("on-disk")]);
+        let listing = &walk.take_listings()[root.path()];
+        assert_eq!(listing.modified, modified);
+        assert_eq!(listing.subdirs, vec![PathBuf::from("on-disk")]);
+    }
+
+    #[test]
+    fn only_settled_directories_are_remembered() {
+        let root = TestDir::new("grace");
+        root.dir("project/.hg");
+        let options = TraverseOptions::default();
+
+        // Just created, so it could still change within the same tick of the clock
+        let walk = Walk::new(root.path(), &options, Arc::default(), Seen::default());
+        assert_eq!(read(&walk, root.path()), vec![root.join("project")]);
+        assert!(walk.take_listings().is_empty());
+
+        settle(root.path());
+        let walk = Walk::new(root.path(), &options, Arc::default(), Seen::default());
+        read(&walk, root.path());
+        assert!(walk.take_listings().contains_key(root.path()));
+
+        // A directory that can't be read isn't remembered as empty
+        let missing = root.join("missing");
+        let walk = Walk::new(root.path(), &options, Arc::default(), Seen::default());
+        assert!(read(&walk, &missing).is_empty());
+        assert_eq!(walk.take_skipped().len(), 1);
+        assert!(walk.take_listings().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn listings_that_arent_utf8_are_never_remembered() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = TestDir::new("not-utf8");
+        let name = OsStr::from_bytes(b"caf\xe9");
+        let dir = root.dir(name);
+        root.dir(dir.join("inner"));
+        settle(&dir);
+        settle(root.path());
+
+        let options = TraverseOptions::default();
+        let walk = Walk::new(root.path(), &options, Arc::default(), Seen::default());
+        // Both the directory holding the name and the directory named by it
+        assert_eq!(read(&walk, root.path()), vec![dir.clone()]);
+        assert_eq!(read(&walk, &dir), vec![dir.join("inner")]);
+        // Still searched, just read again every time
+        assert!(walk.take_listings().is_empty());
     }
 }
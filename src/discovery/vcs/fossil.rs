@@ -0,0 +1,116 @@
+use super::{parse_utc, run, Backend, VcsError};
+use crate::discovery::cache::VcsInfo;
+use std::path::Path;
+
+/// Reads Fossil checkouts with `fossil`
+pub struct Fossil;
+
+impl Backend for Fossil {
+    fn fetch_info(&self, path: &Path) -> Result<VcsInfo, VcsError> {
+        use VcsError::*;
+
+        let info = run("fossil", &["info"], path).or(Err(FailedToOpen))?;
+        let current_branch_name = run("fossil", &["branch", "current"], path)
+            .map(|branch| branch.trim().to_string())
+            .unwrap_or_default();
+        let head = parse_info(&info, current_branch_name)?;
+
+        // Like git, count files that aren't tracked yet as well as changed ones
+        let changes = run("fossil", &["changes"], path).or(Err(FailedToReadStatus))?;
+        let extras = run("fossil", &["extras"], path).unwrap_or_default();
+
+        let remote_url = run("fossil", &["remote"], path)
+            .ok()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty() && url != "off");
+
+        Ok(VcsInfo {
+            uncommitted_changes: changes.lines().count() + extras.lines().count(),
+            remote_url,
+            ..head
+        })
+    }
+}
+
+/// The checkout's last commit and tags from `fossil info`, on `current_branch_name`.
+/// Changes and the remote are left for the caller.
+fn parse_info(info: &str, current_branch_name: String) -> Result<VcsInfo, VcsError> {
+    let field = |name: &str| {
+        info.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+
+    // `checkout: <hash> 2024-01-02 03:04:05 UTC`
+    let last_commit_time = field("checkout")
+        .and_then(|checkout| checkout.split_once(' ').map(|(_, date)| date))
+        .and_then(parse_utc)
+        .ok_or(VcsError::FailedToReadHead)?;
+
+    // `comment: Fix the thing (user: someone)`
+    let last_commit_summary = field("comment").map_or_else(String::new, |comment| {
+        let comment = match comment.rfind(" (user: ") {
+            Some(i) => &comment[..i],
+            None => comment,
+        };
+        comment.lines().next().unwrap_or("").to_string()
+    });
+
+    // The branch is a tag too, so leave it out
+    let head_tags = field("tags").map_or_else(Vec::new, |tags| {
+        tags.split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty() && *tag != current_branch_name)
+            .map(String::from)
+            .collect()
+    });
+
+    Ok(VcsInfo {
+        last_commit_summary,
+        last_commit_time,
+        current_branch_name,
+        uncommitted_changes: 0,
+        ahead_behind: None,
+        remote_url: None,
+        head_tags,
+        out_of_sync: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: &str = "\
+project-name: project
+repository:   /home/me/project.fossil
+local-root:   /home/me/src/project/
+config-db:    /home/me/.config/fossil.db
+project-code: 5e1a0c2b9d7f4e3a8b6c1d0e2f4a6b8c9d0e1f2a
+checkout:     8f3c2a1b0d9e7f6a5b4c3d2e1f0a9b8c7d6e5f4a 2024-01-02 02:04:05 UTC
+parent:       1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b 2023-12-30 10:00:00 UTC
+tags:         trunk, release, v1.0
+comment:      Fix the build (user: someone)
+check-ins:    42
+";
+
+    #[test]
+    fn parses_info() {
+        let info = parse_info(INFO, "trunk".into()).unwrap();
+        assert_eq!(info.last_commit_summary, "Fix the build");
+        assert_eq!(info.last_commit_time, 1704161045);
+        assert_eq!(info.current_branch_name, "trunk");
+        assert_eq!(info.head_tags, vec!["release", "v1.0"]);
+    }
+
+    #[test]
+    fn keeps_the_branch_tag_when_on_another_branch() {
+        let info = parse_info(INFO, "feature".into()).unwrap();
+        assert_eq!(info.head_tags, vec!["trunk", "release", "v1.0"]);
+    }
+
+    #[test]
+    fn info_without_a_checkout_is_an_error() {
+        assert!(parse_info("project-name: project\n", "trunk".into()).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use super::{Backend, VcsError};
+use crate::discovery::cache::VcsInfo;
+use git2::{Branch, Oid, Repository, StatusOptions};
+use serde_derive::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Reads git repositories with libgit2, so it works without `git` installed
+pub struct Git;
+
+impl Backend for Git {
+    fn fetch_info(&self, path: &Path) -> Result<VcsInfo, VcsError> {
+        fetch_vcs_info(path)
+    }
+}
+
+pub fn fetch_vcs_info(path: &Path) -> Result<VcsInfo, VcsError> {
+    use VcsError::*;
+
+    let repo = Repository::open(path).or(Err(FailedToOpen))?;
+    let head = repo.head().or(Err(FailedToReadHead))?;
+
+    // Bare repositories have no working tree to change
+    let uncommitted_changes = match repo.is_bare() {
+        true => 0,
+        false => repo
+            .statuses(Some(
+                StatusOptions::new()
+                    .include_ignored(false)
+                    .include_untracked(true),
+            ))
+            .or(Err(FailedToReadStatus))?
+            .len(),
+    };
+
+    let last_commit = head.peel_to_commit().or(Err(FailedToReadHead))?;
+
+    let vcs_info = VcsInfo {
+        last_commit_summary: last_commit.summary().ok_or(FailedToReadHead)?.to_string(),
+
+        last_commit_time: last_commit.time().seconds(),
+
+        current_branch_name: head.shorthand().ok_or(FailedToReadHead)?.to_string(),
+
+        uncommitted_changes,
+
+        ahead_behind: ahead_behind(&repo),
+
+        remote_url: remote_url(&repo),
+
+        head_tags: head_tags(&repo, last_commit.id()),
+
+        out_of_sync: None,
+    };
+    Ok(vcs_info)
+}
+
+fn ahead_behind(repo: &Repository) -> Option<(usize, usize)> {
+    let branch = Branch::wrap(repo.head().ok()?);
+    let local = branch.get().target()?;
+    let upstream = branch.upstream().ok()?.get().target()?;
+
+    repo.graph_ahead_behind(local, upstream).ok()
+}
+
+fn remote_url(repo: &Repository) -> Option<String> {
+    let remote = match repo.find_remote("origin") {
+        Ok(remote) => remote,
+        Err(_) => {
+            let names = repo.remotes().ok()?;
+            let first = names.iter().flatten().next()?;
+            repo.find_remote(first).ok()?
+        }
+    };
+
+    remote.url().map(String::from)
+}
+
+fn head_tags(repo: &Repository, head: Oid) -> Vec<String> {
+    let references = match repo.references_glob("refs/tags/*") {
+        Ok(references) => references,
+        Err(_) => return vec![],
+    };
+
+    references
+        .flatten()
+        .filter(|r| r.peel_to_commit().is_ok_and(|c| c.id() == head))
+        .filter_map(|r| r.shorthand().map(String::from))
+        .collect()
+}
+
+/// A linked worktree, checked out alongside the main working tree of a repository
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Worktree {
+    /// The repository's main working tree, or the repository itself if it's bare
+    #[serde(with = "crate::daemon::paths")]
+    pub main: PathBuf,
+    /// Branch checked out in the worktree, or `None` if its HEAD is detached
+    pub branch: Option<String>,
+}
+
+/// Read the `.git` file at the top of a linked worktree. Submodules have a `.git`
+/// file too, but aren't worktrees.
+pub fn read_worktree(path: &Path) -> Option<Worktree> {
+    let git_dir = linked_git_dir(path)?;
+
+    // Only worktrees share the objects of a main repository
+    let common_dir = fs::read_to_string(git_dir.join("commondir")).ok()?;
+    let common_dir = normalize(&git_dir.join(common_dir.trim()));
+    let main = match common_dir.file_name() == Some(OsStr::new(".git")) {
+        true => common_dir.parent()?.to_path_buf(),
+        false => common_dir,
+    };
+
+    Some(Worktree {
+        main,
+        branch: head_branch(&git_dir),
+    })
+}
+
+/// The git directory named by the `.git` file at the top of a linked worktree or a
+/// submodule
+pub fn linked_git_dir(path: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(path.join(".git")).ok()?;
+    Some(path.join(contents.strip_prefix("gitdir:")?.trim()))
+}
+
+/// Working trees linked to a repository, given its git directory (`.git`, or the
+/// repository itself if it's bare)
+pub fn linked_worktrees(git_dir: &Path) -> Vec<PathBuf> {
+    let entries = match git_dir.join("worktrees").read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            // Holds the path to the `.git` file at the top of the worktree
+            let git_file = fs::read_to_string(entry.path().join("gitdir")).ok()?;
+            let worktree = Path::new(git_file.trim()).parent()?;
+            Some(worktree.to_path_buf()).filter(|path| path.is_dir())
+        })
+        .collect()
+}
+
+/// Submodules of the repository at `path` that are checked out
+pub fn submodules(path: &Path) -> Vec<PathBuf> {
+    let repo = match Repository::open(path) {
+        Ok(repo) => repo,
+        Err(_) => return vec![],
+    };
+
+    // Joined onto `path` rather than the repository's own idea of its working tree,
+    // so the paths match the ones found by traversing
+    let submodules = repo.submodules().unwrap_or_default();
+    submodules
+        .iter()
+        .map(|submodule| path.join(submodule.path()))
+        .filter(|path| path.join(".git").exists())
+        .collect()
+}
+
+/// Whether the commit checked out in a submodule differs from the one recorded in its
+/// superproject's HEAD
+pub fn submodule_out_of_sync(superproject: &Path, path: &Path) -> Option<bool> {
+    let repo = Repository::open(superproject).ok()?;
+    let relative = path.strip_prefix(superproject).ok()?;
+    let submodule = repo
+        .submodules()
+        .ok()?
+        .into_iter()
+        .find(|submodule| submodule.path() == relative)?;
+
+    Some(submodule.head_id() != submodule.workdir_id())
+}
+
+/// Whether `path` looks like a repository without a working tree, like `name.git`
+pub fn is_bare_repo(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
+}
+
+fn head_branch(git_dir: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(String::from)
+}
+
+/// Resolve `..` and `.` without touching the filesystem, so the path still matches
+/// the one found while traversing even through symlinks
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => (),
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
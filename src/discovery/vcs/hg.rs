@@ -0,0 +1,90 @@
+use super::{run, Backend, VcsError};
+use crate::discovery::cache::VcsInfo;
+use std::path::Path;
+
+/// Reads Mercurial repositories with `hg`
+pub struct Mercurial;
+
+const TEMPLATE: &str = "{desc|firstline}\\n{date|hgdate}\\n{branch}\\n{tags}\\n";
+
+impl Backend for Mercurial {
+    fn fetch_info(&self, path: &Path) -> Result<VcsInfo, VcsError> {
+        let log = run("hg", &["log", "--rev", ".", "--template", TEMPLATE], path)?;
+        let head = parse_log(&log)?;
+
+        let status = run("hg", &["status"], path).or(Err(VcsError::FailedToReadStatus))?;
+
+        let remote_url = run("hg", &["paths", "default"], path)
+            .ok()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+
+        Ok(VcsInfo {
+            uncommitted_changes: status.lines().count(),
+            remote_url,
+            ..head
+        })
+    }
+}
+
+/// What `hg log` printed with `TEMPLATE` about the working copy's parent. Changes and
+/// the remote are left for the caller, and working out the upstream needs the network.
+fn parse_log(log: &str) -> Result<VcsInfo, VcsError> {
+    use VcsError::*;
+
+    let mut lines = log.lines();
+
+    let last_commit_summary = lines.next().ok_or(FailedToReadHead)?.to_string();
+    // `hgdate` is the timestamp followed by the timezone offset
+    let last_commit_time = lines
+        .next()
+        .and_then(|date| date.split_whitespace().next())
+        .and_then(|seconds| seconds.parse().ok())
+        .ok_or(FailedToReadHead)?;
+    let current_branch_name = lines.next().ok_or(FailedToReadHead)?.to_string();
+    let head_tags = lines
+        .next()
+        .unwrap_or("")
+        .split_whitespace()
+        .filter(|tag| *tag != "tip")
+        .map(String::from)
+        .collect();
+
+    Ok(VcsInfo {
+        last_commit_summary,
+        last_commit_time,
+        current_branch_name,
+        uncommitted_changes: 0,
+        ahead_behind: None,
+        remote_url: None,
+        head_tags,
+        out_of_sync: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_log() {
+        let info = parse_log("Fix the build\n1704161045 -3600\ndefault\ntip v1.0\n").unwrap();
+        assert_eq!(info.last_commit_summary, "Fix the build");
+        assert_eq!(info.last_commit_time, 1704161045);
+        assert_eq!(info.current_branch_name, "default");
+        assert_eq!(info.head_tags, vec!["v1.0"]);
+    }
+
+    #[test]
+    fn untagged_commit_has_no_tags() {
+        let info = parse_log("Start\n1704161045 0\nfeature\n\n").unwrap();
+        assert_eq!(info.current_branch_name, "feature");
+        assert!(info.head_tags.is_empty());
+    }
+
+    #[test]
+    fn empty_log_is_an_error() {
+        assert!(parse_log("").is_err());
+        assert!(parse_log("Summary\nnot a date\ndefault\n").is_err());
+    }
+}
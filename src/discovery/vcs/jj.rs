@@ -0,0 +1,146 @@
+use super::{run, Backend, VcsError};
+use crate::discovery::cache::VcsInfo;
+use std::path::Path;
+
+/// Reads Jujutsu repositories with `jj`
+pub struct Jujutsu;
+
+/// One line per commit, with fields separated by tabs
+const TEMPLATE: &str = concat!(
+    "description.first_line() ++ \"\\t\" ",
+    "++ committer.timestamp().format(\"%s\") ++ \"\\t\" ",
+    "++ bookmarks.map(|b| b.name()).join(\",\") ++ \"\\t\" ",
+    "++ tags.map(|t| t.name()).join(\",\") ++ \"\\n\"",
+);
+
+impl Backend for Jujutsu {
+    fn fetch_info(&self, path: &Path) -> Result<VcsInfo, VcsError> {
+        let log = run(
+            "jj",
+            &["log", "--no-graph", "-r", "@ | @-", "-T", TEMPLATE],
+            path,
+        )?;
+        let head = parse_log(&log)?;
+
+        let diff = run("jj", &["diff", "--summary"], path).or(Err(VcsError::FailedToReadStatus))?;
+        let remote_url = run("jj", &["git", "remote", "list"], path)
+            .ok()
+            .and_then(|remotes| parse_remotes(&remotes));
+
+        Ok(VcsInfo {
+            uncommitted_changes: diff.lines().count(),
+            remote_url,
+            ..head
+        })
+    }
+}
+
+/// What `jj log` printed with `TEMPLATE` about the working copy and its parent. Changes
+/// and the remote are left for the caller.
+fn parse_log(log: &str) -> Result<VcsInfo, VcsError> {
+    use VcsError::*;
+
+    // The working copy is usually an empty change on top of the last real one, so
+    // fall back to its parent when it has no description yet
+    let commits: Vec<Vec<&str>> = log.lines().map(|l| l.split('\t').collect()).collect();
+    let commit = commits
+        .iter()
+        .find(|fields| fields.first().is_some_and(|d| !d.is_empty()))
+        .or_else(|| commits.last())
+        .filter(|fields| fields.len() == 4)
+        .ok_or(FailedToReadHead)?;
+
+    let names = |fields: &str| -> Vec<String> {
+        fields
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    let bookmarks: Vec<String> = commits
+        .iter()
+        .filter_map(|fields| fields.get(2))
+        .flat_map(|field| names(field))
+        .collect();
+
+    Ok(VcsInfo {
+        last_commit_summary: commit[0].to_string(),
+        last_commit_time: commit[1].parse().or(Err(FailedToReadHead))?,
+        current_branch_name: bookmarks.first().cloned().unwrap_or_default(),
+        uncommitted_changes: 0,
+        ahead_behind: None,
+        remote_url: None,
+        head_tags: names(commit[3]),
+        out_of_sync: None,
+    })
+}
+
+/// The URL of `origin` in `jj git remote list`, if there is one, otherwise the first
+/// remote's
+fn parse_remotes(remotes: &str) -> Option<String> {
+    let remotes: Vec<(&str, &str)> = remotes
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ' ');
+            Some((parts.next()?, parts.next()?.trim()))
+        })
+        .collect();
+
+    remotes
+        .iter()
+        .find(|(name, _)| *name == "origin")
+        .or_else(|| remotes.first())
+        .map(|(_, url)| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_the_parent_when_the_working_copy_has_no_description() {
+        let log = "\t1704200000\t\t\nFix the build\t1704161045\tmain\tv1.0\n";
+        let info = parse_log(log).unwrap();
+        assert_eq!(info.last_commit_summary, "Fix the build");
+        assert_eq!(info.last_commit_time, 1704161045);
+        assert_eq!(info.current_branch_name, "main");
+        assert_eq!(info.head_tags, vec!["v1.0"]);
+    }
+
+    #[test]
+    fn uses_the_working_copy_once_it_has_a_description() {
+        let log = "Work in progress\t1704200000\tfeature\t\nFix the build\t1704161045\tmain\t\n";
+        let info = parse_log(log).unwrap();
+        assert_eq!(info.last_commit_summary, "Work in progress");
+        assert_eq!(info.last_commit_time, 1704200000);
+        assert_eq!(info.current_branch_name, "feature");
+        assert!(info.head_tags.is_empty());
+    }
+
+    #[test]
+    fn new_repository_has_an_empty_head() {
+        let info = parse_log("\t0\t\t\n").unwrap();
+        assert_eq!(info.last_commit_summary, "");
+        assert_eq!(info.current_branch_name, "");
+    }
+
+    #[test]
+    fn malformed_log_is_an_error() {
+        assert!(parse_log("").is_err());
+        assert!(parse_log("Summary\tyesterday\t\t\n").is_err());
+    }
+
+    #[test]
+    fn prefers_origin() {
+        let remotes = "backup /mnt/backup/repo\norigin https://github.com/x/y.git\n";
+        assert_eq!(
+            parse_remotes(remotes).as_deref(),
+            Some("https://github.com/x/y.git")
+        );
+        assert_eq!(
+            parse_remotes("upstream https://example.com/y.git\n").as_deref(),
+            Some("https://example.com/y.git")
+        );
+        assert_eq!(parse_remotes(""), None);
+    }
+}
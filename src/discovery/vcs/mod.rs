@@ -0,0 +1,206 @@
+pub mod fossil;
+pub mod git;
+pub mod hg;
+pub mod jj;
+pub mod svn;
+
+use super::cache::{Project, VcsInfo};
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VcsKind {
+    Git,
+    Mercurial,
+    Jujutsu,
+    Subversion,
+    Fossil,
+}
+
+/// Entries that mark the top of a repository, in order of preference. Jujutsu comes
+/// before git since it's often colocated with a `.git` directory.
+const MARKERS: &[(&str, VcsKind)] = &[
+    (".jj", VcsKind::Jujutsu),
+    (".git", VcsKind::Git),
+    (".hg", VcsKind::Mercurial),
+    (".svn", VcsKind::Subversion),
+    (".fslckout", VcsKind::Fossil),
+    ("_FOSSIL_", VcsKind::Fossil),
+];
+
+impl VcsKind {
+    /// Short name shown in the VCS column
+    pub fn name(self) -> &'static str {
+        match self {
+            VcsKind::Git => "git",
+            VcsKind::Mercurial => "hg",
+            VcsKind::Jujutsu => "jj",
+            VcsKind::Subversion => "svn",
+            VcsKind::Fossil => "fossil",
+        }
+    }
+
+    /// The entries at the top of a repository that mark it as this kind
+    pub fn markers(self) -> impl Iterator<Item = &'static str> {
+        MARKERS
+            .iter()
+            .filter(move |(_, kind)| *kind == self)
+            .map(|(marker, _)| *marker)
+    }
+
+    fn preference(self) -> usize {
+        MARKERS
+            .iter()
+            .position(|(_, kind)| *kind == self)
+            .unwrap_or(0)
+    }
+
+    /// The kind of repository an entry in a directory marks, if any
+    pub fn from_marker(file_name: &str) -> Option<VcsKind> {
+        MARKERS
+            .iter()
+            .find(|(marker, _)| *marker == file_name)
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Of two markers found in the same directory, the one that describes it best
+    pub fn preferred(self, other: VcsKind) -> VcsKind {
+        if other.preference() < self.preference() {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// The kind of repository at the top of `path`, if it is one
+    pub fn detect(path: &Path) -> Option<VcsKind> {
+        MARKERS
+            .iter()
+            .find(|(marker, _)| path.join(marker).exists())
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn backend(self) -> &'static dyn Backend {
+        match self {
+            VcsKind::Git => &git::Git,
+            VcsKind::Mercurial => &hg::Mercurial,
+            VcsKind::Jujutsu => &jj::Jujutsu,
+            VcsKind::Subversion => &svn::Subversion,
+            VcsKind::Fossil => &fossil::Fossil,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum VcsError {
+    FailedToOpen,
+    FailedToReadHead,
+    FailedToReadStatus,
+    /// The command line tool for this kind of repository isn't installed
+    CommandNotFound,
+    CommandFailed,
+}
+
+/// The status of a project's repository. `None` if it isn't one, or it couldn't be read.
+pub fn fetch_project_info(project: &Project) -> Option<VcsInfo> {
+    let mut info = project.vcs?.backend().fetch_info(&project.path).ok()?;
+    if let Some(superproject) = &project.superproject {
+        info.out_of_sync = git::submodule_out_of_sync(superproject, &project.path);
+    }
+    Some(info)
+}
+
+/// Reads the state of one kind of repository
+pub trait Backend: Sync {
+    fn fetch_info(&self, path: &Path) -> Result<VcsInfo, VcsError>;
+}
+
+/// Run a command line tool in `path` and return what it printed
+fn run(program: &str, args: &[&str], path: &Path) -> Result<String, VcsError> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(path)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => VcsError::CommandNotFound,
+            _ => VcsError::CommandFailed,
+        })?;
+
+    if !output.status.success() {
+        return Err(VcsError::CommandFailed);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Seconds since the epoch for a UTC date written `YYYY-MM-DD HH:MM:SS`, with either a
+/// space or a `T` in the middle
+fn parse_utc(date: &str) -> Option<i64> {
+    let date = date.get(..19)?;
+    let field = |range: std::ops::Range<usize>| date.get(range)?.parse::<i64>().ok();
+
+    let days = days_from_civil(field(0..4)?, field(5..7)?, field(8..10)?);
+    Some(days * 86400 + field(11..13)? * 3600 + field(14..16)? * 60 + field(17..19)?)
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn parses_utc_dates() {
+        assert_eq!(parse_utc("1970-01-01 00:00:00"), Some(0));
+        assert_eq!(parse_utc("2024-01-02 02:04:05"), Some(1704161045));
+        assert_eq!(parse_utc("2024-01-02T02:04:05Z"), Some(1704161045));
+        assert_eq!(parse_utc("2024-01-02"), None);
+        assert_eq!(parse_utc("yesterday at noon!!"), None);
+    }
+
+    #[test]
+    fn counts_days_across_leap_years() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(
+            days_from_civil(2000, 3, 1) - days_from_civil(2000, 2, 28),
+            2
+        );
+        assert_eq!(
+            days_from_civil(1900, 3, 1) - days_from_civil(1900, 2, 28),
+            1
+        );
+        assert_eq!(days_from_civil(2024, 1, 1), 19723);
+    }
+
+    #[test]
+    fn detects_repositories_by_marker() {
+        let root = TestDir::new("detect");
+        root.dir("hg/.hg");
+        root.dir("svn/.svn");
+        root.file("fossil/_FOSSIL_", "");
+        // A colocated jj repository has a `.git` too
+        root.dir("jj/.jj");
+        root.dir("jj/.git");
+        root.dir("plain");
+
+        assert_eq!(VcsKind::detect(&root.join("hg")), Some(VcsKind::Mercurial));
+        assert_eq!(
+            VcsKind::detect(&root.join("svn")),
+            Some(VcsKind::Subversion)
+        );
+        assert_eq!(VcsKind::detect(&root.join("fossil")), Some(VcsKind::Fossil));
+        assert_eq!(VcsKind::detect(&root.join("jj")), Some(VcsKind::Jujutsu));
+        assert_eq!(VcsKind::detect(&root.join("plain")), None);
+    }
+}
@@ -0,0 +1,173 @@
+use super::{parse_utc, run, Backend, VcsError};
+use crate::discovery::cache::VcsInfo;
+use std::path::Path;
+
+/// Reads Subversion working copies with `svn`
+pub struct Subversion;
+
+impl Backend for Subversion {
+    fn fetch_info(&self, path: &Path) -> Result<VcsInfo, VcsError> {
+        let info = run("svn", &["info"], path).or(Err(VcsError::FailedToOpen))?;
+        let head = parse_info(&info)?;
+
+        let status = run("svn", &["status"], path).or(Err(VcsError::FailedToReadStatus))?;
+
+        Ok(VcsInfo {
+            last_commit_summary: last_commit_summary(path).unwrap_or_default(),
+            uncommitted_changes: status.lines().filter(|l| !l.trim().is_empty()).count(),
+            ..head
+        })
+    }
+}
+
+/// The last commit, branch and repository from `svn info`. The summary and changes are
+/// left for the caller.
+fn parse_info(info: &str) -> Result<VcsInfo, VcsError> {
+    let field = |name: &str| {
+        info.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            .map(str::trim)
+    };
+
+    let last_commit_time = field("Last Changed Date")
+        .and_then(parse_date)
+        .ok_or(VcsError::FailedToReadHead)?;
+
+    Ok(VcsInfo {
+        last_commit_summary: String::new(),
+        last_commit_time,
+        current_branch_name: field("Relative URL").map_or_else(String::new, branch_name),
+        uncommitted_changes: 0,
+        ahead_behind: None,
+        remote_url: field("Repository Root").map(String::from),
+        // Tags are just copies in the repository, nothing points at a revision
+        head_tags: vec![],
+        out_of_sync: None,
+    })
+}
+
+/// The message of the last commit to the working copy. This asks the server, so it's
+/// left empty when it can't be reached.
+fn last_commit_summary(path: &Path) -> Option<String> {
+    let log = run(
+        "svn",
+        &["log", "--limit", "1", "--revision", "COMMITTED"],
+        path,
+    )
+    .ok()?;
+
+    parse_log_summary(&log)
+}
+
+/// The first line of the message in `svn log` output
+fn parse_log_summary(log: &str) -> Option<String> {
+    // A dashed line, `r12 | author | date | 1 line`, a blank line, then the message
+    log.lines()
+        .skip_while(|line| !line.starts_with('r') || !line.contains(" | "))
+        .nth(2)
+        .map(String::from)
+}
+
+/// `2024-01-02 03:04:05 +0100 (Tue, 02 Jan 2024)` as a timestamp
+fn parse_date(date: &str) -> Option<i64> {
+    let local = parse_utc(date)?;
+    let offset = date.get(20..25)?;
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let hours: i64 = offset.get(1..3)?.parse().ok()?;
+    let minutes: i64 = offset.get(3..5)?.parse().ok()?;
+
+    Some(local - sign * (hours * 3600 + minutes * 60))
+}
+
+/// The branch a working copy's relative URL points into, following the usual
+/// `trunk`, `branches` and `tags` layout
+fn branch_name(relative_url: &str) -> String {
+    let parts: Vec<&str> = relative_url
+        .trim_start_matches('^')
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match parts.iter().position(|p| *p == "branches" || *p == "tags") {
+        Some(i) if i + 1 < parts.len() => parts[i + 1].to_string(),
+        _ if parts.contains(&"trunk") => String::from("trunk"),
+        _ => parts.last().map_or_else(String::new, |p| p.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: &str = "\
+Path: .
+Working Copy Root Path: /home/me/src/project
+URL: https://svn.example.com/repos/project/branches/feature-x
+Relative URL: ^/branches/feature-x
+Repository Root: https://svn.example.com/repos/project
+Repository UUID: 0b6e3d0a-9c2f-4f3e-8d7a-2a9c1f0e5b11
+Revision: 1234
+Node Kind: directory
+Schedule: normal
+Last Changed Author: someone
+Last Changed Rev: 1230
+Last Changed Date: 2024-01-02 03:04:05 +0100 (Tue, 02 Jan 2024)
+";
+
+    const LOG: &str = "\
+------------------------------------------------------------------------
+r1230 | someone | 2024-01-02 03:04:05 +0100 (Tue, 02 Jan 2024) | 2 lines
+
+Fix the build
+And explain why
+------------------------------------------------------------------------
+";
+
+    #[test]
+    fn parses_info() {
+        let info = parse_info(INFO).unwrap();
+        assert_eq!(info.last_commit_time, 1704161045);
+        assert_eq!(info.current_branch_name, "feature-x");
+        assert_eq!(
+            info.remote_url.as_deref(),
+            Some("https://svn.example.com/repos/project")
+        );
+    }
+
+    #[test]
+    fn info_without_a_date_is_an_error() {
+        assert!(parse_info("Path: .\nRevision: 0\n").is_err());
+    }
+
+    #[test]
+    fn parses_the_log_summary() {
+        assert_eq!(parse_log_summary(LOG).as_deref(), Some("Fix the build"));
+        assert_eq!(parse_log_summary(""), None);
+    }
+
+    #[test]
+    fn applies_the_offset() {
+        let utc = parse_date("2024-01-02 02:04:05 +0000 (Tue, 02 Jan 2024)");
+        assert_eq!(utc, Some(1704161045));
+        assert_eq!(
+            parse_date("2024-01-02 03:04:05 +0100 (Tue, 02 Jan 2024)"),
+            utc
+        );
+        assert_eq!(
+            parse_date("2024-01-01 21:34:05 -0430 (Mon, 01 Jan 2024)"),
+            utc
+        );
+        assert_eq!(parse_date("2024-01-02 02:04:05"), None);
+    }
+
+    #[test]
+    fn follows_the_usual_layout() {
+        assert_eq!(branch_name("^/trunk"), "trunk");
+        assert_eq!(branch_name("^/trunk/src"), "trunk");
+        assert_eq!(branch_name("^/branches/feature-x"), "feature-x");
+        assert_eq!(branch_name("^/project/branches/feature-x/src"), "feature-x");
+        assert_eq!(branch_name("^/tags/v1.0"), "v1.0");
+        assert_eq!(branch_name("^/somewhere/else"), "else");
+        assert_eq!(branch_name("^/"), "");
+    }
+}
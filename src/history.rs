@@ -0,0 +1,107 @@
+use crate::config::{ReadError, WriteError};
+use dirs::home_dir;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Visit {
+    /// Number of times the project has been selected
+    pub count: u32,
+
+    /// Unix timestamp of the most recent selection
+    pub last: u64,
+}
+
+/// Record of which projects have been selected, and when
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    /// Location of the history file
+    #[serde(skip)]
+    pub location: PathBuf,
+
+    #[serde(default)]
+    visits: HashMap<PathBuf, Visit>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl History {
+    /// Load the history from the home directory. A missing or unreadable history
+    /// is treated as empty, it'll be recreated on the next visit.
+    pub fn autoload() -> History {
+        let location = match home_dir() {
+            Some(home) => home.join(".prj_history"),
+            None => return History::default(),
+        };
+
+        History::load_from(&location).unwrap_or(History {
+            location,
+            visits: HashMap::new(),
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<History, ReadError> {
+        if !path.exists() {
+            return Err(ReadError::Missing);
+        }
+
+        let mut f = File::open(path).or(Err(ReadError::IoError))?;
+        let mut buffer = Vec::new();
+        f.read_to_end(&mut buffer).or(Err(ReadError::IoError))?;
+
+        let mut history: History = toml::from_slice(&buffer).or(Err(ReadError::ParseError))?;
+        history.location = path.into();
+
+        Ok(history)
+    }
+
+    pub fn write(&self) -> Result<(), WriteError> {
+        let output = toml::to_vec(self).or(Err(WriteError::SerializeError))?;
+        let mut f = File::create(&self.location).map_err(WriteError::IoError)?;
+
+        f.write_all(&output).map_err(WriteError::IoError)
+    }
+
+    pub fn record_visit(&mut self, path: &Path) {
+        // TOML can only hold UTF-8, and one path that isn't would stop the whole
+        // history from being saved
+        if path.to_str().is_none() {
+            return;
+        }
+
+        let visit = self.visits.entry(path.to_path_buf()).or_default();
+        visit.count += 1;
+        visit.last = now();
+    }
+
+    pub fn last_visited(&self, path: &Path) -> Option<u64> {
+        self.visits.get(path).map(|v| v.last)
+    }
+
+    /// How often and how recently a project has been selected, in the style of `z`.
+    /// Recent visits count for more, so old favourites fade out over time.
+    pub fn frecency(&self, path: &Path) -> f64 {
+        let visit = match self.visits.get(path) {
+            Some(visit) => visit,
+            None => return 0.0,
+        };
+
+        let age = now().saturating_sub(visit.last);
+        let weight = match age {
+            a if a < 60 * 60 => 4.0,
+            a if a < 60 * 60 * 24 => 2.0,
+            a if a < 60 * 60 * 24 * 7 => 0.5,
+            _ => 0.25,
+        };
+        visit.count as f64 * weight
+    }
+}
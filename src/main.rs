@@ -1,9 +1,25 @@
 mod commands;
 mod config;
+mod daemon;
 mod discovery;
+mod history;
+mod matching;
+#[cfg(test)]
+mod test_dir;
 
-use clap::{App, SubCommand};
+use clap::{App, Arg, SubCommand};
+use commands::alias::AliasError;
+use commands::cd::theme::ColorChoice;
+use commands::cd::tree::View;
+use commands::cd::{CommandError, ListOptions};
+use commands::daemon::DaemonError;
+use commands::path::PathError;
+use commands::pin::PinError;
+use commands::tag::TagError;
 use config::Config;
+use matching::sort::SortMode;
+use matching::{CaseMode, MatchMode};
+use std::path::Path;
 use std::process::exit;
 
 fn main() {
@@ -12,16 +28,359 @@ fn main() {
         .author("Jordan West")
         .about("Manage your local git projects")
         .subcommand(SubCommand::with_name("configure").about("Create a configuration"))
-        .subcommand(SubCommand::with_name("list").about("Select a project"))
+        .subcommand(
+            SubCommand::with_name("daemon")
+                .about("Keep the projects up to date in the background for list, ls and path"),
+        )
+        .subcommand(
+            SubCommand::with_name("ls")
+                .about("Print the path of every project")
+                .arg(
+                    Arg::with_name("diagnostics")
+                        .long("diagnostics")
+                        .help("Report directories that couldn't be searched on stderr"),
+                )
+                .arg(
+                    Arg::with_name("full-rescan")
+                        .long("full-rescan")
+                        .help("Read every directory, even the ones that haven't changed since the last search"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("alias")
+                .about("Manage short names for projects and other directories")
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Add or replace an alias")
+                        .arg(Arg::with_name("name").required(true))
+                        .arg(
+                            Arg::with_name("path")
+                                .help("Directory to point at, defaults to the current one"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("rm")
+                        .about("Remove an alias")
+                        .arg(Arg::with_name("name").required(true)),
+                )
+                .subcommand(SubCommand::with_name("ls").about("List the aliases")),
+        )
+        .subcommand(
+            SubCommand::with_name("tag")
+                .about("Manage project tags, used to filter with `tag:name` in the query")
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Tag a project")
+                        .arg(Arg::with_name("tag").required(true))
+                        .arg(
+                            Arg::with_name("path")
+                                .help("Directory to tag, defaults to the current one"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("rm")
+                        .about("Remove a tag from a project")
+                        .arg(Arg::with_name("tag").required(true))
+                        .arg(
+                            Arg::with_name("path")
+                                .help("Directory to untag, defaults to the current one"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("ls")
+                        .about("List the tags on a project, or every tag and its projects")
+                        .arg(Arg::with_name("path")),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("pin")
+                .about("Always list a project first when the query is empty")
+                .arg(Arg::with_name("path").help("Directory to pin, defaults to the current one")),
+        )
+        .subcommand(
+            SubCommand::with_name("unpin")
+                .about("Stop listing a project first")
+                .arg(
+                    Arg::with_name("path").help("Directory to unpin, defaults to the current one"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("path")
+                .about("Print the path of the project that best matches a name or query")
+                .arg(
+                    Arg::with_name("query")
+                        .multiple(true)
+                        .required(true)
+                        .help("Name or query to look up"),
+                )
+                .arg(
+                    Arg::with_name("full-rescan")
+                        .long("full-rescan")
+                        .help("Read every directory, even the ones that haven't changed since the last search"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("Select a project")
+                .arg(
+                    Arg::with_name("query")
+                        .multiple(true)
+                        .help("Start with this query"),
+                )
+                .arg(
+                    Arg::with_name("select-1")
+                        .long("select-1")
+                        .short("1")
+                        .help("Print the project without asking if it's the only match"),
+                )
+                .arg(
+                    Arg::with_name("exit-0")
+                        .long("exit-0")
+                        .short("0")
+                        .help("Exit without asking if nothing matches"),
+                )
+                .arg(
+                    Arg::with_name("mode")
+                        .long("mode")
+                        .takes_value(true)
+                        .possible_values(MatchMode::NAMES)
+                        .help("How the query is matched against projects"),
+                )
+                .arg(
+                    Arg::with_name("case")
+                        .long("case")
+                        .takes_value(true)
+                        .possible_values(CaseMode::NAMES)
+                        .help("Case sensitivity of the query"),
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(SortMode::NAMES)
+                        .help("Order of the results"),
+                )
+                .arg(
+                    Arg::with_name("view")
+                        .long("view")
+                        .takes_value(true)
+                        .possible_values(View::NAMES)
+                        .help("Show a flat list or a tree grouped by host and owner"),
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(ColorChoice::NAMES)
+                        .help("Whether to use colour, auto respects NO_COLOR"),
+                )
+                .arg(
+                    Arg::with_name("full-rescan")
+                        .long("full-rescan")
+                        .help("Read every directory, even the ones that haven't changed since the last search"),
+                ),
+        )
         .get_matches();
 
     if let Some(_) = matches.subcommand_matches("configure") {
         commands::configure::configure().unwrap();
-    } else if let Some(_) = matches.subcommand_matches("list") {
+    } else if let Some(list_matches) = matches.subcommand_matches("list") {
+        let mut config = Config::autoload().unwrap();
+
+        if let Some(mode) = list_matches.value_of("mode").and_then(MatchMode::from_name) {
+            config.match_mode = mode;
+        }
+        if let Some(case) = list_matches.value_of("case").and_then(CaseMode::from_name) {
+            config.case_mode = case;
+        }
+        if let Some(sort) = list_matches.value_of("sort").and_then(SortMode::from_name) {
+            config.sort = sort;
+        }
+        if let Some(view) = list_matches.value_of("view").and_then(View::from_name) {
+            config.view = view;
+        }
+        if let Some(color) = list_matches
+            .value_of("color")
+            .and_then(ColorChoice::from_name)
+        {
+            config.color = color;
+        }
+
+        let options = ListOptions {
+            query: list_matches
+                .values_of("query")
+                .map_or_else(String::new, |words| words.collect::<Vec<_>>().join(" ")),
+            select_one: list_matches.is_present("select-1"),
+            exit_zero: list_matches.is_present("exit-0"),
+            full_rescan: list_matches.is_present("full-rescan"),
+        };
+
+        match commands::cd::run(&config, &options) {
+            Ok(()) => (),
+            Err(CommandError::NotATerminal) => {
+                eprintln!("prj list needs a terminal to draw on, try `prj ls` instead");
+                exit(1);
+            }
+            Err(_) => exit(1),
+        }
+    } else if matches.subcommand_matches("daemon").is_some() {
+        let config = Config::autoload().unwrap();
+
+        match commands::daemon::run(config) {
+            Ok(()) => (),
+            Err(DaemonError::HomeDirNotFound) => {
+                eprintln!("Couldn't find the home directory to put the socket in");
+                exit(1);
+            }
+            Err(DaemonError::AlreadyRunning(socket)) => {
+                eprintln!("A daemon is already listening on {}", socket.display());
+                exit(1);
+            }
+            Err(DaemonError::FailedToListen(e)) => {
+                eprintln!("Couldn't listen on the socket: {}", e);
+                exit(1);
+            }
+            #[cfg(not(unix))]
+            Err(DaemonError::Unsupported) => {
+                eprintln!("prj daemon needs Unix sockets, which this platform doesn't have");
+                exit(1);
+            }
+        }
+    } else if let Some(ls_matches) = matches.subcommand_matches("ls") {
+        let config = Config::autoload().unwrap();
+        commands::ls::run(
+            &config,
+            ls_matches.is_present("diagnostics"),
+            ls_matches.is_present("full-rescan"),
+        );
+    } else if let Some(alias_matches) = matches.subcommand_matches("alias") {
+        let mut config = Config::autoload().unwrap();
+
+        let result = match alias_matches.subcommand() {
+            ("add", Some(add_matches)) => {
+                let name = add_matches.value_of("name").unwrap();
+                let path = add_matches.value_of("path").map(Path::new);
+                commands::alias::add(&mut config, name, path)
+                    .map(|path| println!("{} -> {}", name, path.display()))
+            }
+            ("rm", Some(rm_matches)) => {
+                commands::alias::remove(&mut config, rm_matches.value_of("name").unwrap())
+            }
+            ("ls", _) => {
+                commands::alias::list(&config);
+                Ok(())
+            }
+            _ => {
+                println!("{}", alias_matches.usage());
+                exit(1)
+            }
+        };
+
+        match result {
+            Ok(()) => (),
+            Err(AliasError::NotFound) => {
+                eprintln!("There's no alias with that name");
+                exit(1);
+            }
+            Err(AliasError::NotADirectory(path)) => {
+                eprintln!("{} isn't a directory", path.display());
+                exit(1);
+            }
+            Err(AliasError::WriteError(e)) => {
+                eprintln!("Couldn't save the config: {:?}", e);
+                exit(1);
+            }
+        }
+    } else if let Some(tag_matches) = matches.subcommand_matches("tag") {
+        let mut config = Config::autoload().unwrap();
+
+        let result = match tag_matches.subcommand() {
+            ("add", Some(add_matches)) => commands::tag::add(
+                &mut config,
+                add_matches.value_of("tag").unwrap(),
+                add_matches.value_of("path").map(Path::new),
+            )
+            .map(|_| ()),
+            ("rm", Some(rm_matches)) => commands::tag::remove(
+                &mut config,
+                rm_matches.value_of("tag").unwrap(),
+                rm_matches.value_of("path").map(Path::new),
+            )
+            .map(|_| ()),
+            ("ls", Some(ls_matches)) => {
+                commands::tag::list(&config, ls_matches.value_of("path").map(Path::new))
+            }
+            _ => {
+                println!("{}", tag_matches.usage());
+                exit(1)
+            }
+        };
+
+        match result {
+            Ok(()) => (),
+            Err(TagError::NotTagged) => {
+                eprintln!("That directory doesn't have that tag");
+                exit(1);
+            }
+            Err(TagError::TaggedByMarker(marker)) => {
+                eprintln!("That tag is set in {}, remove it there", marker.display());
+                exit(1);
+            }
+            Err(TagError::NotADirectory(path)) => {
+                eprintln!("{} isn't a directory", path.display());
+                exit(1);
+            }
+            Err(TagError::WriteError(e)) => {
+                eprintln!("Couldn't save the config: {:?}", e);
+                exit(1);
+            }
+        }
+    } else if let (name @ "pin", Some(pin_matches)) | (name @ "unpin", Some(pin_matches)) =
+        matches.subcommand()
+    {
+        let mut config = Config::autoload().unwrap();
+        let path = pin_matches.value_of("path").map(Path::new);
+
+        let result = match name {
+            "pin" => commands::pin::pin(&mut config, path),
+            _ => commands::pin::unpin(&mut config, path),
+        };
+
+        match result {
+            Ok(path) if name == "pin" => println!("Pinned {}", path.display()),
+            Ok(path) => println!("Unpinned {}", path.display()),
+            Err(PinError::NotPinned) => {
+                eprintln!("That directory isn't pinned");
+                exit(1);
+            }
+            Err(PinError::NotADirectory(path)) => {
+                eprintln!("{} isn't a directory", path.display());
+                exit(1);
+            }
+            Err(PinError::WriteError(e)) => {
+                eprintln!("Couldn't save the config: {:?}", e);
+                exit(1);
+            }
+        }
+    } else if let Some(path_matches) = matches.subcommand_matches("path") {
         let config = Config::autoload().unwrap();
+        let query = path_matches
+            .values_of("query")
+            .map_or_else(String::new, |words| words.collect::<Vec<_>>().join(" "));
 
-        if let Err(_) = commands::cd::run(&config) {
-            exit(1);
+        match commands::path::resolve(&config, &query, path_matches.is_present("full-rescan")) {
+            Ok(path) => commands::print_path(&path),
+            Err(PathError::NoMatch) => {
+                eprintln!("No project matches `{}`", query);
+                exit(1);
+            }
+            Err(PathError::Ambiguous(candidates)) => {
+                eprintln!("`{}` matches several projects equally well:", query);
+                for candidate in candidates {
+                    eprintln!("  {}", candidate);
+                }
+                exit(1);
+            }
         }
     } else {
         println!("{}", matches.usage());
@@ -0,0 +1,522 @@
+pub mod query;
+pub mod sort;
+
+use crate::discovery::cache::Project;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use query::{Term, TermKind};
+use regex::{Regex, RegexBuilder};
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Score given for each character of a literal (non-fuzzy) match
+const SCORE_MATCH: i64 = 16;
+
+/// Extra score for a literal match that starts at the beginning of a path segment
+const BONUS_BOUNDARY: i64 = 8;
+
+/// Extra score for a term that matches within the project's own directory name
+const BONUS_BASENAME: i64 = 32;
+
+/// Extra score for matching a project's alias, enough to put it ahead of matches on paths
+const BONUS_ALIAS: i64 = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    #[default]
+    Fuzzy,
+    Exact,
+    Prefix,
+    Regex,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseMode {
+    /// Case insensitive unless the term contains an uppercase character
+    #[default]
+    Smart,
+    Insensitive,
+    Sensitive,
+}
+
+impl MatchMode {
+    pub const NAMES: &'static [&'static str] = &["fuzzy", "exact", "prefix", "regex"];
+
+    pub fn from_name(name: &str) -> Option<MatchMode> {
+        match name {
+            "fuzzy" => Some(MatchMode::Fuzzy),
+            "exact" => Some(MatchMode::Exact),
+            "prefix" => Some(MatchMode::Prefix),
+            "regex" => Some(MatchMode::Regex),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchMode::Fuzzy => "fuzzy",
+            MatchMode::Exact => "exact",
+            MatchMode::Prefix => "prefix",
+            MatchMode::Regex => "regex",
+        }
+    }
+
+    pub fn next(self) -> MatchMode {
+        match self {
+            MatchMode::Fuzzy => MatchMode::Exact,
+            MatchMode::Exact => MatchMode::Prefix,
+            MatchMode::Prefix => MatchMode::Regex,
+            MatchMode::Regex => MatchMode::Fuzzy,
+        }
+    }
+}
+
+impl CaseMode {
+    pub const NAMES: &'static [&'static str] = &["smart", "insensitive", "sensitive"];
+
+    pub fn from_name(name: &str) -> Option<CaseMode> {
+        match name {
+            "smart" => Some(CaseMode::Smart),
+            "insensitive" => Some(CaseMode::Insensitive),
+            "sensitive" => Some(CaseMode::Sensitive),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CaseMode::Smart => "smart-case",
+            CaseMode::Insensitive => "ignore-case",
+            CaseMode::Sensitive => "case-sensitive",
+        }
+    }
+
+    pub fn next(self) -> CaseMode {
+        match self {
+            CaseMode::Smart => CaseMode::Insensitive,
+            CaseMode::Insensitive => CaseMode::Sensitive,
+            CaseMode::Sensitive => CaseMode::Smart,
+        }
+    }
+
+    fn is_sensitive(self, pattern: &str) -> bool {
+        match self {
+            CaseMode::Smart => pattern.chars().any(char::is_uppercase),
+            CaseMode::Insensitive => false,
+            CaseMode::Sensitive => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MatchResult {
+    /// With the tags from the config added to the ones from its `.prj.toml`
+    pub project: Project,
+    pub display: String,
+    pub score: i64,
+
+    /// Position of the project's root in the config
+    pub root_index: usize,
+    pub dirty: bool,
+    pub last_commit_time: Option<i64>,
+    pub last_visited: Option<u64>,
+
+    /// Name the project is known by in the config's aliases
+    pub alias: Option<String>,
+    pub pinned: bool,
+}
+
+/// A query compiled for a particular match mode, ready to score candidates
+pub struct Matcher {
+    mode: MatchMode,
+    case: CaseMode,
+    terms: Vec<Term>,
+    /// `tag:` terms, which filter on the project's tags rather than being scored
+    tag_terms: Vec<Term>,
+    regex: Option<Regex>,
+    fuzzy: SkimMatcherV2,
+}
+
+impl Matcher {
+    pub fn new(query: &str, mode: MatchMode, case: CaseMode) -> Matcher {
+        let (terms, regex) = match mode {
+            // The whole query is the pattern, whitespace and all
+            MatchMode::Regex => (
+                vec![],
+                RegexBuilder::new(query)
+                    .case_insensitive(!case.is_sensitive(query))
+                    .build()
+                    .ok(),
+            ),
+            _ => (query::parse(query), None),
+        };
+        let (tag_terms, terms) = terms.into_iter().partition(|t| t.kind == TermKind::Tag);
+
+        Matcher {
+            mode,
+            case,
+            terms,
+            tag_terms,
+            regex,
+            fuzzy: SkimMatcherV2::default(),
+        }
+    }
+
+    /// Score a candidate against the query, or `None` if it doesn't match.
+    /// An invalid regex matches nothing.
+    pub fn score(&self, candidate: &str) -> Option<i64> {
+        if self.mode == MatchMode::Regex {
+            let regex = self.regex.as_ref()?;
+            let found = regex.find(candidate)?;
+            let len = found.as_str().chars().count();
+            let bonus = match regex.is_match(basename(candidate)) {
+                true => BONUS_BASENAME,
+                false => 0,
+            };
+            return Some(literal_score(candidate, found.start(), len) + bonus);
+        }
+
+        let mut total = 0;
+        for term in &self.terms {
+            match (self.score_term(term, candidate), term.negated) {
+                (Some(score), false) => total += score + self.basename_bonus(term, candidate),
+                (None, true) => (),
+                _ => return None,
+            }
+        }
+
+        Some(total)
+    }
+
+    /// Score a project by both its path and its alias, preferring a match on the alias.
+    /// Projects without the tags asked for with `tag:` don't match at all.
+    pub fn score_project(
+        &self,
+        display: &str,
+        alias: Option<&str>,
+        tags: &[String],
+    ) -> Option<i64> {
+        let tags_match = self.tag_terms.iter().all(|term| {
+            let has_tag = tags.iter().any(|tag| tag.eq_ignore_ascii_case(&term.text));
+            has_tag != term.negated
+        });
+        if !tags_match {
+            return None;
+        }
+
+        // A negated term has to miss both, or it could be dodged through the other one
+        let excluded = self.terms.iter().filter(|term| term.negated).any(|term| {
+            std::iter::once(display)
+                .chain(alias)
+                .any(|candidate| self.score_term(term, candidate).is_some())
+        });
+        if excluded {
+            return None;
+        }
+
+        // Every alias matches a query with nothing to look for, so it earns nothing then
+        let alias_score = match self.has_positive_terms() {
+            true => alias.and_then(|a| self.score(a)).map(|s| s + BONUS_ALIAS),
+            false => None,
+        };
+        self.score(display).max(alias_score)
+    }
+
+    fn has_positive_terms(&self) -> bool {
+        match self.mode {
+            MatchMode::Regex => self.regex.as_ref().is_some_and(|r| !r.as_str().is_empty()),
+            _ => self.terms.iter().any(|term| !term.negated),
+        }
+    }
+
+    fn score_term(&self, term: &Term, candidate: &str) -> Option<i64> {
+        let sensitive = self.case.is_sensitive(&term.text);
+
+        match (term.kind, self.mode) {
+            (TermKind::Default, MatchMode::Fuzzy) => {
+                self.fuzzy_score(candidate, &term.text, sensitive)
+            }
+            (TermKind::Default, MatchMode::Prefix) => {
+                prefix_score(basename(candidate), &term.text, sensitive)
+            }
+            (TermKind::Default, _) | (TermKind::Exact, _) => {
+                exact_score(candidate, &term.text, sensitive)
+            }
+            (TermKind::Prefix, _) => prefix_score(candidate, &term.text, sensitive),
+            (TermKind::Suffix, _) => suffix_score(candidate, &term.text, sensitive),
+            // Tags aren't part of the candidate, they're checked in `score_project`
+            (TermKind::Tag, _) => None,
+        }
+    }
+
+    /// Favour projects whose own name matches the term, rather than a match spread
+    /// across the directories above it
+    fn basename_bonus(&self, term: &Term, candidate: &str) -> i64 {
+        let name = basename(candidate);
+        let already_basename = term.kind == TermKind::Default && self.mode == MatchMode::Prefix;
+
+        if already_basename || name.len() == candidate.len() {
+            return 0;
+        }
+
+        match term.kind {
+            TermKind::Default | TermKind::Exact => match self.score_term(term, name) {
+                Some(_) => BONUS_BASENAME,
+                None => 0,
+            },
+            TermKind::Prefix | TermKind::Suffix | TermKind::Tag => 0,
+        }
+    }
+
+    fn fuzzy_score(&self, candidate: &str, pattern: &str, sensitive: bool) -> Option<i64> {
+        // The skim matcher is only case sensitive when the pattern contains uppercase
+        // characters, so the other cases have to be handled here
+        if !sensitive {
+            return self.fuzzy.fuzzy_match(candidate, &pattern.to_lowercase());
+        }
+
+        if !is_subsequence(candidate, pattern) {
+            return None;
+        }
+        self.fuzzy.fuzzy_match(candidate, pattern)
+    }
+}
+
+fn basename(candidate: &str) -> &str {
+    Path::new(candidate)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(candidate)
+}
+
+fn fold_case(s: &str, sensitive: bool) -> Cow<'_, str> {
+    match sensitive {
+        true => Cow::Borrowed(s),
+        false => Cow::Owned(s.to_lowercase()),
+    }
+}
+
+fn is_subsequence(candidate: &str, pattern: &str) -> bool {
+    let mut candidate_chars = candidate.chars();
+    pattern
+        .chars()
+        .all(|p| candidate_chars.by_ref().any(|c| c == p))
+}
+
+fn literal_score(candidate: &str, start: usize, len: usize) -> i64 {
+    let at_boundary = candidate[..start]
+        .chars()
+        .last()
+        .is_none_or(|c| "/\\-_.:".contains(c));
+
+    let bonus = if at_boundary { BONUS_BOUNDARY } else { 0 };
+    SCORE_MATCH * len as i64 + bonus
+}
+
+fn exact_score(candidate: &str, pattern: &str, sensitive: bool) -> Option<i64> {
+    let haystack = fold_case(candidate, sensitive);
+    let needle = fold_case(pattern, sensitive);
+    let start = haystack.find(needle.as_ref())?;
+
+    Some(literal_score(&haystack, start, needle.chars().count()))
+}
+
+fn prefix_score(candidate: &str, pattern: &str, sensitive: bool) -> Option<i64> {
+    let needle = fold_case(pattern, sensitive);
+    if !fold_case(candidate, sensitive).starts_with(needle.as_ref()) {
+        return None;
+    }
+
+    Some(literal_score(candidate, 0, needle.chars().count()))
+}
+
+fn suffix_score(candidate: &str, pattern: &str, sensitive: bool) -> Option<i64> {
+    let needle = fold_case(pattern, sensitive);
+    if !fold_case(candidate, sensitive).ends_with(needle.as_ref()) {
+        return None;
+    }
+
+    Some(SCORE_MATCH * needle.chars().count() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(query: &str, mode: MatchMode, case: CaseMode, candidate: &str) -> bool {
+        Matcher::new(query, mode, case).score(candidate).is_some()
+    }
+
+    fn fuzzy(query: &str, candidate: &str) -> bool {
+        matches(query, MatchMode::Fuzzy, CaseMode::Smart, candidate)
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(
+            Matcher::new("", MatchMode::Fuzzy, CaseMode::Smart).score("a/b"),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn default_terms_use_the_match_mode() {
+        assert!(fuzzy("gthbprj", "github.com/jordwest/prj"));
+        assert!(!matches(
+            "gthb",
+            MatchMode::Exact,
+            CaseMode::Smart,
+            "github.com/x"
+        ));
+        assert!(matches(
+            "hub",
+            MatchMode::Exact,
+            CaseMode::Smart,
+            "github.com/x"
+        ));
+        // Prefix mode matches the start of the project's own name
+        assert!(matches(
+            "pr",
+            MatchMode::Prefix,
+            CaseMode::Smart,
+            "github.com/x/prj"
+        ));
+        assert!(!matches(
+            "git",
+            MatchMode::Prefix,
+            CaseMode::Smart,
+            "github.com/x/prj"
+        ));
+    }
+
+    #[test]
+    fn exact_prefix_and_suffix_terms() {
+        assert!(fuzzy("'west", "github.com/jordwest/prj"));
+        assert!(!fuzzy("'wset", "github.com/jordwest/prj"));
+        assert!(fuzzy("^github", "github.com/jordwest/prj"));
+        assert!(!fuzzy("^jord", "github.com/jordwest/prj"));
+        assert!(fuzzy("prj$", "github.com/jordwest/prj"));
+        assert!(!fuzzy("west$", "github.com/jordwest/prj"));
+    }
+
+    #[test]
+    fn negated_terms_exclude_matches() {
+        assert!(!fuzzy("!west", "github.com/jordwest/prj"));
+        assert!(fuzzy("!gitlab", "github.com/jordwest/prj"));
+        assert!(!fuzzy("!^github", "github.com/jordwest/prj"));
+        assert!(fuzzy("!^jord", "github.com/jordwest/prj"));
+        assert!(!fuzzy("!prj$", "github.com/jordwest/prj"));
+        assert!(fuzzy("prj !^gitlab", "github.com/jordwest/prj"));
+    }
+
+    #[test]
+    fn every_term_has_to_match() {
+        assert!(fuzzy("jord prj", "github.com/jordwest/prj"));
+        assert!(!fuzzy("jord xyz", "github.com/jordwest/prj"));
+    }
+
+    #[test]
+    fn smart_case_is_sensitive_only_with_uppercase() {
+        assert!(fuzzy("prj", "github.com/x/PRJ"));
+        assert!(fuzzy("PRJ", "github.com/x/PRJ"));
+        assert!(!fuzzy("PRJ", "github.com/x/prj"));
+        assert!(fuzzy("'Prj", "github.com/x/Prj"));
+        assert!(!fuzzy("'Prj", "github.com/x/prj"));
+    }
+
+    #[test]
+    fn case_modes() {
+        assert!(matches(
+            "PRJ",
+            MatchMode::Fuzzy,
+            CaseMode::Insensitive,
+            "x/prj"
+        ));
+        assert!(!matches(
+            "prj",
+            MatchMode::Fuzzy,
+            CaseMode::Sensitive,
+            "x/PRJ"
+        ));
+        assert!(matches(
+            "prj",
+            MatchMode::Exact,
+            CaseMode::Sensitive,
+            "x/prj"
+        ));
+    }
+
+    #[test]
+    fn regex_mode_uses_the_whole_query() {
+        let regex = |query, candidate| matches(query, MatchMode::Regex, CaseMode::Smart, candidate);
+        assert!(regex(r"jord\w+/p", "github.com/jordwest/prj"));
+        assert!(regex("^github.*prj$", "github.com/jordwest/prj"));
+        assert!(!regex("^prj", "github.com/jordwest/prj"));
+        // Whitespace is part of the pattern rather than separating terms
+        assert!(!regex("jord prj", "github.com/jordwest/prj"));
+        assert!(!regex("JORD", "github.com/jordwest/prj"));
+        assert!(regex("jord", "github.com/JORDwest/prj"));
+    }
+
+    #[test]
+    fn invalid_regex_matches_nothing() {
+        assert!(!matches("(", MatchMode::Regex, CaseMode::Smart, "("));
+    }
+
+    #[test]
+    fn basename_matches_score_higher() {
+        let matcher = Matcher::new("'prj", MatchMode::Fuzzy, CaseMode::Smart);
+        let in_name = matcher.score("github.com/x/prj").unwrap();
+        let in_path = matcher.score("prj.com/x/other").unwrap();
+        assert!(in_name > in_path);
+    }
+
+    #[test]
+    fn tag_terms_filter_on_tags() {
+        let matcher = Matcher::new("tag:work", MatchMode::Fuzzy, CaseMode::Smart);
+        assert!(matcher
+            .score_project("x/prj", None, &["Work".into()])
+            .is_some());
+        assert!(matcher.score_project("x/prj", None, &[]).is_none());
+
+        let matcher = Matcher::new("!tag:old", MatchMode::Fuzzy, CaseMode::Smart);
+        assert!(matcher
+            .score_project("x/prj", None, &["old".into()])
+            .is_none());
+        assert!(matcher.score_project("x/prj", None, &[]).is_some());
+    }
+
+    #[test]
+    fn aliases_only_score_when_they_match() {
+        for query in &["", "tag:work", "!old"] {
+            let matcher = Matcher::new(query, MatchMode::Fuzzy, CaseMode::Smart);
+            assert_eq!(
+                matcher.score_project("x/prj", Some("web"), &["work".into()]),
+                Some(0),
+                "{:?}",
+                query
+            );
+        }
+
+        let matcher = Matcher::new("prj", MatchMode::Fuzzy, CaseMode::Smart);
+        let by_path = matcher.score_project("x/prj", Some("web"), &[]).unwrap();
+        assert!(by_path < BONUS_ALIAS);
+        let by_alias = matcher.score_project("x/other", Some("prj"), &[]).unwrap();
+        assert!(by_alias > BONUS_ALIAS);
+    }
+
+    #[test]
+    fn negated_terms_check_the_alias_too() {
+        let matcher = Matcher::new("!web", MatchMode::Fuzzy, CaseMode::Smart);
+        assert!(matcher.score_project("x/prj", Some("web"), &[]).is_none());
+        assert!(matcher.score_project("x/web", Some("prj"), &[]).is_none());
+        assert!(matcher.score_project("x/prj", Some("api"), &[]).is_some());
+
+        let matcher = Matcher::new("prj !web", MatchMode::Fuzzy, CaseMode::Smart);
+        assert!(matcher.score_project("x/web", Some("prj"), &[]).is_none());
+        assert!(matcher.score_project("x/prj", Some("web"), &[]).is_none());
+    }
+}
@@ -0,0 +1,133 @@
+/// How a single term of the query should be matched against a candidate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TermKind {
+    /// Use whichever match mode is currently selected
+    Default,
+    /// `'term` - the term must appear as an exact substring
+    Exact,
+    /// `^term` - the candidate must start with the term
+    Prefix,
+    /// `term$` - the candidate must end with the term
+    Suffix,
+    /// `tag:term` - the project must have this tag
+    Tag,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub text: String,
+    pub kind: TermKind,
+    pub negated: bool,
+}
+
+/// Split a query into fzf-style terms.
+///
+/// Terms are separated by whitespace and all of them must match. Each term may use:
+///
+///   'exact    exact substring match
+///   ^prefix   candidate starts with the term
+///   suffix$   candidate ends with the term
+///   !term     candidate must *not* contain the term (combines with ^ and $)
+///   tag:name  project has the tag (or doesn't, with !)
+pub fn parse(query: &str) -> Vec<Term> {
+    query.split_whitespace().filter_map(parse_term).collect()
+}
+
+fn parse_term(raw: &str) -> Option<Term> {
+    let mut text = raw;
+    let mut negated = false;
+    let mut kind = TermKind::Default;
+
+    if text.starts_with('!') {
+        negated = true;
+        text = &text[1..];
+    }
+
+    if text.starts_with("tag:") {
+        kind = TermKind::Tag;
+        text = &text[4..];
+    } else if text.starts_with('\'') {
+        kind = TermKind::Exact;
+        text = &text[1..];
+    } else if text.starts_with('^') {
+        kind = TermKind::Prefix;
+        text = &text[1..];
+    } else if text.ends_with('$') {
+        kind = TermKind::Suffix;
+        text = &text[..text.len() - 1];
+    }
+
+    // A negated term is always matched literally, the same way fzf does it
+    if negated && kind == TermKind::Default {
+        kind = TermKind::Exact;
+    }
+
+    // Ignore operators that don't have any text after them yet (eg. while typing)
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(Term {
+        text: text.to_string(),
+        kind,
+        negated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(text: &str, kind: TermKind, negated: bool) -> Term {
+        Term {
+            text: text.to_string(),
+            kind,
+            negated,
+        }
+    }
+
+    #[test]
+    fn parses_each_kind_of_term() {
+        assert_eq!(parse("foo"), vec![term("foo", TermKind::Default, false)]);
+        assert_eq!(parse("'foo"), vec![term("foo", TermKind::Exact, false)]);
+        assert_eq!(parse("^foo"), vec![term("foo", TermKind::Prefix, false)]);
+        assert_eq!(parse("foo$"), vec![term("foo", TermKind::Suffix, false)]);
+        assert_eq!(parse("tag:work"), vec![term("work", TermKind::Tag, false)]);
+    }
+
+    #[test]
+    fn splits_terms_on_whitespace() {
+        assert_eq!(
+            parse("  api\t^github  rs$ "),
+            vec![
+                term("api", TermKind::Default, false),
+                term("github", TermKind::Prefix, false),
+                term("rs", TermKind::Suffix, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn negated_terms_are_literal() {
+        assert_eq!(parse("!foo"), vec![term("foo", TermKind::Exact, true)]);
+        assert_eq!(parse("!'foo"), vec![term("foo", TermKind::Exact, true)]);
+        assert_eq!(parse("!tag:old"), vec![term("old", TermKind::Tag, true)]);
+    }
+
+    #[test]
+    fn negation_combines_with_prefix_and_suffix() {
+        assert_eq!(parse("!^foo"), vec![term("foo", TermKind::Prefix, true)]);
+        assert_eq!(parse("!foo$"), vec![term("foo", TermKind::Suffix, true)]);
+    }
+
+    #[test]
+    fn ignores_operators_without_text_while_typing() {
+        assert_eq!(parse("!"), vec![]);
+        assert_eq!(parse("'"), vec![]);
+        assert_eq!(parse("^"), vec![]);
+        assert_eq!(parse("$"), vec![]);
+        assert_eq!(parse("!^"), vec![]);
+        assert_eq!(parse("tag:"), vec![]);
+        assert_eq!(parse("api !"), vec![term("api", TermKind::Default, false)]);
+    }
+}
@@ -0,0 +1,87 @@
+use super::MatchResult;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    /// Best match first
+    #[default]
+    Score,
+    /// Alphabetical by displayed path
+    Name,
+    /// Most recently committed to first
+    LastCommit,
+    /// Most recently selected in prj first
+    LastVisited,
+    /// Projects with uncommitted changes first
+    DirtyFirst,
+    /// In the order the roots appear in the config
+    Root,
+}
+
+impl SortMode {
+    pub const NAMES: &'static [&'static str] = &[
+        "score",
+        "name",
+        "last-commit",
+        "last-visited",
+        "dirty-first",
+        "root",
+    ];
+
+    pub fn from_name(name: &str) -> Option<SortMode> {
+        match name {
+            "score" => Some(SortMode::Score),
+            "name" => Some(SortMode::Name),
+            "last-commit" => Some(SortMode::LastCommit),
+            "last-visited" => Some(SortMode::LastVisited),
+            "dirty-first" => Some(SortMode::DirtyFirst),
+            "root" => Some(SortMode::Root),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortMode::Score => "score",
+            SortMode::Name => "name",
+            SortMode::LastCommit => "last-commit",
+            SortMode::LastVisited => "last-visited",
+            SortMode::DirtyFirst => "dirty-first",
+            SortMode::Root => "root",
+        }
+    }
+
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Score => SortMode::Name,
+            SortMode::Name => SortMode::LastCommit,
+            SortMode::LastCommit => SortMode::LastVisited,
+            SortMode::LastVisited => SortMode::DirtyFirst,
+            SortMode::DirtyFirst => SortMode::Root,
+            SortMode::Root => SortMode::Score,
+        }
+    }
+}
+
+pub fn sort_results(results: &mut [MatchResult], mode: SortMode) {
+    results.sort_by(|a, b| {
+        let primary = match mode {
+            SortMode::Score => b.score.cmp(&a.score),
+            SortMode::Name => a.display.cmp(&b.display),
+            // `None` sorts before `Some`, so reversing puts unknown times last
+            SortMode::LastCommit => b.last_commit_time.cmp(&a.last_commit_time),
+            SortMode::LastVisited => b.last_visited.cmp(&a.last_visited),
+            SortMode::DirtyFirst => b.dirty.cmp(&a.dirty),
+            SortMode::Root => a.root_index.cmp(&b.root_index),
+        };
+
+        // Pinned projects win ties, then they're broken the same way every time so a
+        // query always gives the same order
+        primary
+            .then_with(|| b.pinned.cmp(&a.pinned))
+            .then_with(|| b.score.cmp(&a.score))
+            .then_with(|| a.display.cmp(&b.display))
+            .then_with(|| a.project.path.cmp(&b.project.path))
+    });
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A new empty directory for a test to build a tree in. It's removed when dropped, so
+/// it's cleaned up even when an assert fails.
+pub struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    pub fn new(name: &str) -> TestDir {
+        // Tests run in parallel, and can ask for the same name
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "prj-{}-{}-{}",
+            name,
+            std::process::id(),
+            CREATED.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, relative: P) -> PathBuf {
+        self.path.join(relative)
+    }
+
+    /// Create a directory and any missing parents
+    pub fn dir<P: AsRef<Path>>(&self, relative: P) -> PathBuf {
+        let path = self.join(relative);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    /// Write a file, creating its directory if it's missing
+    pub fn file<P: AsRef<Path>>(&self, relative: P, contents: &str) -> PathBuf {
+        let path = self.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Create a symlink at `relative` pointing to `target`, which is taken as it is, and
+    /// its directory if it's missing
+    #[cfg(unix)]
+    pub fn symlink<P: AsRef<Path>, T: AsRef<Path>>(&self, relative: P, target: T) -> PathBuf {
+        let path = self.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        std::os::unix::fs::symlink(target, &path).unwrap();
+        path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}